@@ -0,0 +1,171 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use directories_next::ProjectDirs;
+
+use crate::common::{check_file, sha1_file, RinthaError};
+
+/// A jar in the cache, stored under the sha1 of its contents.
+#[derive(Debug)]
+pub struct CacheEntry {
+    pub sha1: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Where downloaded jars are kept, shared by every profile. Jars live in `sha1/<hash>`, while
+/// `sha512/<hash>` only holds the sha1 of the same jar so it can be found by either hash.
+pub fn cache_dir() -> Result<PathBuf, RinthaError> {
+    if let Some(proj_dirs) = ProjectDirs::from("rs", "", "rintha") {
+        Ok(proj_dirs.cache_dir().to_owned())
+    } else {
+        Err(RinthaError::UnsupportedPlatform)
+    }
+}
+
+fn object_path(sha1: &str) -> Result<PathBuf, RinthaError> {
+    Ok(cache_dir()?.join("sha1").join(sha1))
+}
+
+fn index_path(sha512: &str) -> Result<PathBuf, RinthaError> {
+    Ok(cache_dir()?.join("sha512").join(sha512))
+}
+
+/// Hashes are used as file names, so anything that isn't plain hex is never looked up.
+fn is_hash(hash: &str) -> bool {
+    !hash.is_empty() && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Finds the jar with the sha1 `sha1`, a cached jar that doesn't hash to it anymore is thrown
+/// out instead of being handed out.
+pub fn lookup(sha1: &str) -> Result<Option<CacheEntry>, RinthaError> {
+    if !is_hash(sha1) {
+        return Ok(None);
+    }
+
+    let path = object_path(sha1)?;
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    if sha1_file(&path)? != sha1 {
+        fs::remove_file(&path)?;
+        return Ok(None);
+    }
+
+    Ok(Some(CacheEntry {
+        sha1: sha1.into(),
+        size: fs::metadata(&path)?.len(),
+        path,
+    }))
+}
+
+/// Finds a jar by the hashes modrinth publishes for a file, a jar is only handed out if it
+/// matches the strongest of them.
+pub fn lookup_hashes(hashes: &HashMap<String, String>) -> Result<Option<CacheEntry>, RinthaError> {
+    let entry = match (hashes.get("sha512"), hashes.get("sha1")) {
+        (Some(sha512), _) if is_hash(sha512) && index_path(sha512)?.is_file() => {
+            lookup(fs::read_to_string(index_path(sha512)?)?.trim())?
+        }
+        (_, Some(sha1)) => lookup(sha1)?,
+        _ => None,
+    };
+
+    Ok(entry.filter(|entry| check_file(&entry.path, hashes).is_ok()))
+}
+
+/// Copies the jar at `path` into the cache and returns where it ended up, `sha1` and
+/// `sha512` have to be the hashes of its contents.
+pub fn insert(path: &Path, sha1: &str, sha512: &str) -> Result<CacheEntry, RinthaError> {
+    let target = object_path(sha1)?;
+    // safe to unwrap, object_path always has a parent
+    fs::create_dir_all(target.parent().unwrap())?;
+
+    if !target.is_file() {
+        // renamed into place so a jar in the cache is always complete
+        let partial = target.with_extension("partial");
+        fs::copy(path, &partial)?;
+        fs::rename(&partial, &target)?;
+    }
+
+    if is_hash(sha512) {
+        let index = index_path(sha512)?;
+        fs::create_dir_all(index.parent().unwrap())?;
+        fs::write(index, sha1)?;
+    }
+
+    Ok(CacheEntry {
+        sha1: sha1.into(),
+        size: fs::metadata(&target)?.len(),
+        path: target,
+    })
+}
+
+/// Every jar in the cache, sorted by hash.
+pub fn entries() -> Result<Vec<CacheEntry>, RinthaError> {
+    let dir = cache_dir()?.join("sha1");
+    let mut entries = vec![];
+
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let sha1 = entry.file_name().to_string_lossy().to_string();
+            if !is_hash(sha1.as_str()) {
+                continue;
+            }
+
+            entries.push(CacheEntry {
+                sha1,
+                size: entry.metadata()?.len(),
+                path: entry.path(),
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| a.sha1.cmp(&b.sha1));
+    Ok(entries)
+}
+
+/// Deletes every jar whose sha1 isn't in `keep`, along with the sha512 entries pointing at
+/// them, and returns what was deleted.
+pub fn prune(keep: &HashSet<&str>) -> Result<Vec<CacheEntry>, RinthaError> {
+    let mut removed = vec![];
+    for entry in entries()? {
+        if !keep.contains(entry.sha1.as_str()) {
+            fs::remove_file(&entry.path)?;
+            removed.push(entry);
+        }
+    }
+
+    let index_dir = cache_dir()?.join("sha512");
+    if index_dir.is_dir() {
+        for entry in fs::read_dir(index_dir)? {
+            let path = entry?.path();
+            let sha1 = fs::read_to_string(&path)?;
+            if !object_path(sha1.trim())?.is_file() {
+                fs::remove_file(path)?;
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Deletes the whole cache, the profiles keep their own copies of their jars.
+pub fn clear() -> Result<(), RinthaError> {
+    let dir = cache_dir()?;
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+/// Hard links `source` to `target` so a jar that's in the cache and in profiles is only
+/// stored once, falling back to a copy where links aren't possible (e.g. across filesystems).
+pub fn link_or_copy(source: &Path, target: &Path) -> Result<(), RinthaError> {
+    if fs::hard_link(source, target).is_err() {
+        fs::copy(source, target)?;
+    }
+    Ok(())
+}
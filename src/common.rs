@@ -1,20 +1,44 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env::consts::OS;
+use std::fmt;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
 
 use directories_next::ProjectDirs;
-pub use femtorinth::data_structures::{ModID, ModReleaseType, Version, VersionID};
-pub use femtorinth::version_list;
+use downloader::{Download, Downloader, Verification};
+pub use femtorinth::data_structures::{
+    ModID, ModReleaseType, ModSearchResults, ModSideRequirement, Version, VersionID,
+};
+pub use femtorinth::{mod_get, user_get, version_get, version_list};
 use serde::{Deserialize, Serialize};
+use sha1::Digest;
 use shellexpand::tilde;
 use thiserror::Error;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::cache;
+use crate::transaction::Transaction;
+
+pub const DEFAULT_PARALLEL_DOWNLOADS: u16 = 4;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FullConfig {
+    /// Mods directory to use instead of the default `.minecraft/mods`, `~` is expanded
     pub custom_mod_dir: Option<String>,
     pub current_profile: String,
     pub config_revision: usize,
+    /// How many files are downloaded at the same time
+    pub parallel_downloads: u16,
+    /// Refuse to install files modrinth didn't publish a hash for
+    pub require_hashes: bool,
     pub profiles: HashMap<String, Profile>,
+    /// Set from the command line for a single invocation, never saved
+    #[serde(skip)]
+    pub mod_dir_override: Option<PathBuf>,
+    /// Whether downloads draw progress bars on stderr, off unless a frontend turns it on
+    #[serde(skip)]
+    pub show_progress: bool,
 }
 
 impl Default for FullConfig {
@@ -22,34 +46,274 @@ impl Default for FullConfig {
         FullConfig {
             custom_mod_dir: None,
             current_profile: "default".into(),
-            config_revision: 0,
+            config_revision: crate::config::CONFIG_REVISION,
+            parallel_downloads: DEFAULT_PARALLEL_DOWNLOADS,
+            require_hashes: false,
             profiles: HashMap::from([("default".into(), Profile::default())]),
+            mod_dir_override: None,
+            show_progress: false,
         }
     }
 }
 
 impl FullConfig {
+    /// The mods directory rintha manages, in order of preference that's the one given on the
+    /// command line, `custom_mod_dir` and finally the platform's default.
+    pub fn mod_dir(&self) -> Result<PathBuf, RinthaError> {
+        if let Some(dir) = &self.mod_dir_override {
+            Ok(dir.clone())
+        } else if let Some(dir) = &self.custom_mod_dir {
+            Ok(PathBuf::from(tilde(dir.as_str()).as_ref()))
+        } else {
+            default_mod_dir()
+        }
+    }
+
     pub fn get_current_prof_path(&self) -> Result<PathBuf, RinthaError> {
-        Ok(profile_dir()?.join(self.current_profile.as_str()))
+        self.get_prof_path(self.current_profile.as_str())
+    }
+
+    pub fn get_prof_path(&self, name: &str) -> Result<PathBuf, RinthaError> {
+        Ok(profile_dir()?.join(name))
+    }
+
+    pub fn get_profile(&self, name: &str) -> Result<&Profile, RinthaError> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| RinthaError::ProfileNotFound(name.into()))
+    }
+
+    /// Works out what has to change in the mods directory for it to hold exactly the mods of
+    /// the profile `name`, nothing is touched until the plan is passed to `apply_sync`.
+    pub fn plan_sync(&self, name: &str) -> Result<SyncPlan, RinthaError> {
+        let profile = self.get_profile(name)?;
+        let prof_dir = self.get_prof_path(name)?;
+        let mod_directory = self.mod_dir()?;
+
+        // every file any profile has installed, anything else in the mods dir isn't ours
+        let managed: HashSet<&str> = self
+            .profiles
+            .values()
+            .flat_map(|prof| prof.mods.iter().flatten())
+            .map(|cmod| cmod.current_filename.as_str())
+            .collect();
+        // disabled mods are managed too, so a copy left in the mods dir gets removed
+        let wanted: HashMap<&str, &ConfigMod> = profile
+            .mods
+            .iter()
+            .flatten()
+            .filter(|cmod| !cmod.disabled)
+            .map(|cmod| (cmod.current_filename.as_str(), cmod))
+            .collect();
+
+        let mut plan = SyncPlan::default();
+        let mut present: HashSet<String> = HashSet::new();
+
+        if mod_directory.is_dir() {
+            for entry in fs::read_dir(&mod_directory)? {
+                let path = entry?.path();
+                if !is_jar(&path) {
+                    continue;
+                }
+
+                // is_jar already made sure there's a file name
+                let filename = path.file_name().unwrap().to_string_lossy().to_string();
+                if let Some(cmod) = wanted.get(filename.as_str()) {
+                    // a stale or tampered copy gets replaced by the one in the profile
+                    if sha1_file(&path)? == cmod.sha1 {
+                        present.insert(filename);
+                    }
+                } else if managed.contains(filename.as_str()) {
+                    plan.remove.push(path);
+                } else {
+                    plan.unmanaged.push(path);
+                }
+            }
+        }
+
+        for (filename, cmod) in &wanted {
+            if !present.contains(*filename) {
+                // the cached copy is checked against the manifest, the profile's isn't
+                let source = match cache::lookup(cmod.sha1.as_str())? {
+                    Some(cached) => cached.path,
+                    None => prof_dir.join(filename),
+                };
+                plan.copy.push((source, mod_directory.join(filename)));
+            }
+        }
+
+        Ok(plan)
+    }
+}
+
+impl FullConfig {
+    /// Rehashes every file of the profile `name` in both the profile directory and the mods
+    /// directory and reports anything that doesn't match the manifest.
+    pub fn verify_profile(&self, name: &str) -> Result<Vec<Drift>, RinthaError> {
+        let mut drift = self.verify_dir(name, self.get_prof_path(name)?, false)?;
+        drift.extend(self.verify_dir(name, self.mod_dir()?, true)?);
+        Ok(drift)
+    }
+
+    /// Like `verify_profile`, but only checks the mods directory. Meant for manifests whose
+    /// profile directory isn't on this machine, like one checked into a repository.
+    pub fn verify_mod_dir(&self, name: &str) -> Result<Vec<Drift>, RinthaError> {
+        self.verify_dir(name, self.mod_dir()?, true)
+    }
+
+    /// Checks the jars of the profile `name` in `dir`, which is the mods directory if
+    /// `is_mod_dir` is set and holds no disabled mods then.
+    fn verify_dir(
+        &self,
+        name: &str,
+        dir: PathBuf,
+        is_mod_dir: bool,
+    ) -> Result<Vec<Drift>, RinthaError> {
+        let profile = self.get_profile(name)?;
+        let mut drift = vec![];
+        let mut known: HashSet<&str> = HashSet::new();
+
+        // disabled mods only live in the profile directory, any copy in the mods dir is extra
+        for cmod in profile
+            .mods
+            .iter()
+            .flatten()
+            .filter(|cmod| !(cmod.disabled && is_mod_dir))
+        {
+            known.insert(cmod.current_filename.as_str());
+
+            let path = dir.join(cmod.current_filename.as_str());
+            let kind = if !path.is_file() {
+                DriftKind::Missing
+            } else {
+                let actual = sha1_file(&path)?;
+                if actual == cmod.sha1 {
+                    continue;
+                }
+                DriftKind::Modified { actual }
+            };
+
+            drift.push(Drift {
+                kind,
+                path,
+                mod_id: Some(cmod.id.clone()),
+            });
+        }
+
+        if dir.is_dir() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                // is_jar already made sure there's a file name
+                if is_jar(&path)
+                    && !known.contains(path.file_name().unwrap().to_string_lossy().as_ref())
+                {
+                    drift.push(Drift {
+                        kind: DriftKind::Extra,
+                        path,
+                        mod_id: None,
+                    });
+                }
+            }
+        }
+
+        Ok(drift)
+    }
+}
+
+/// A file that doesn't match the manifest, see `FullConfig::verify_profile`.
+#[derive(Debug)]
+pub struct Drift {
+    pub kind: DriftKind,
+    pub path: PathBuf,
+    /// The mod the file belongs to, `None` for extra files
+    pub mod_id: Option<ModID>,
+}
+
+#[derive(Debug)]
+pub enum DriftKind {
+    Missing,
+    /// The file exists but hashes to `actual` instead of the recorded sha1
+    Modified {
+        actual: String,
+    },
+    /// A jar that isn't part of the profile
+    Extra,
+}
+
+/// The changes needed to make the mods directory match a profile, see `FullConfig::plan_sync`.
+#[derive(Debug, Default)]
+pub struct SyncPlan {
+    /// Jars installed by rintha for other profiles
+    pub remove: Vec<PathBuf>,
+    /// (cache or profile directory source, mods directory destination) pairs
+    pub copy: Vec<(PathBuf, PathBuf)>,
+    /// Jars that no profile knows about, these are never touched by `apply_sync`
+    pub unmanaged: Vec<PathBuf>,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.remove.is_empty() && self.copy.is_empty()
+    }
+}
+
+/// Adds the changes of `plan` to `tx`, nothing happens until it's committed.
+pub fn apply_sync(plan: &SyncPlan, tx: &mut Transaction) {
+    for path in &plan.remove {
+        tx.remove(path.clone());
+    }
+    for (from, to) in &plan.copy {
+        tx.place(from, to.clone());
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Profile {
     pub name: String,
+    /// The minecraft version this profile is played on, if it was set
+    #[serde(default)]
+    pub game_version: Option<String>,
+    /// The mod loader this profile is played with, if it was set
+    #[serde(default)]
+    pub loader: Option<Loader>,
     pub mods: Option<Vec<ConfigMod>>,
+    /// The bisect going on in this profile, if there is one
+    #[serde(default)]
+    pub bisect: Option<Bisect>,
 }
 
 impl Default for Profile {
     fn default() -> Self {
         Profile {
             name: "default".into(),
+            game_version: None,
+            loader: None,
             mods: None,
+            bisect: None,
         }
     }
 }
 
 impl Profile {
+    pub fn new(name: &str, game_version: Option<String>, loader: Option<Loader>) -> Self {
+        Profile {
+            name: name.into(),
+            game_version,
+            loader,
+            mods: None,
+            bisect: None,
+        }
+    }
+
+    /// Whether `ver` can be played with this profile's minecraft version and mod loader.
+    pub fn supports(&self, ver: &Version) -> bool {
+        supports(
+            ver,
+            self.game_version.as_deref(),
+            self.loader.map(Loader::as_str),
+        )
+    }
+
     pub fn add_mod(&mut self, cmod: ConfigMod) -> Result<(), RinthaError> {
         let mods = &self.mods;
         if mods.is_some() {
@@ -67,20 +331,340 @@ impl Profile {
     }
 }
 
+/// Where a `rintha bisect` stands, see `ops::bisect`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Bisect {
+    /// The mods that were disabled when the bisect started, `reset` disables exactly these
+    pub disabled_before: Vec<ModID>,
+    /// The mods that could still be the one breaking the game
+    pub suspects: Vec<ModID>,
+    /// The mods that are enabled for the current step
+    pub enabled: Vec<ModID>,
+    /// How many steps have been marked good or bad so far
+    pub steps: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Loader {
+    Fabric,
+    Forge,
+    Quilt,
+}
+
+impl Loader {
+    /// The name modrinth uses for this loader
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Loader::Fabric => "fabric",
+            Loader::Forge => "forge",
+            Loader::Quilt => "quilt",
+        }
+    }
+}
+
+impl FromStr for Loader {
+    type Err = RinthaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fabric" => Ok(Loader::Fabric),
+            "forge" => Ok(Loader::Forge),
+            "quilt" => Ok(Loader::Quilt),
+            _ => Err(RinthaError::UnknownLoader(s.into())),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ConfigMod {
     pub id: ModID,
+    /// Empty for mods installed before revision 4 of the config
+    pub slug: String,
     pub title: String,
     pub author_username: String,
     pub small_description: String,
     pub latest_mc_ver: String,
     pub license: String,
     pub sha1: String,
+    pub sha512: String,
     pub installed_version_id: VersionID,
     pub installed_version_number: String,
     pub installed_version_type: ModReleaseType,
     pub supported_game_versions: Vec<String>,
+    pub loaders: Vec<String>,
     pub current_filename: String,
+    /// IDs of the mods this version requires
+    pub dependencies: Vec<ModID>,
+    /// Whether the mod was asked for, rather than pulled in as a dependency
+    pub explicit: bool,
+    /// The mod this one was installed as a dependency of, `None` if it's explicit
+    pub installed_for: Option<ModID>,
+    /// Held at the installed version, updates skip it and `get` won't replace it
+    pub pinned: bool,
+    /// Kept in the profile directory but left out of the mods directory
+    pub disabled: bool,
+}
+
+impl ConfigMod {
+    /// A manifest entry for `ver` of the mod described by `info`, the hashes are left empty
+    /// until the file has actually been downloaded. Fails with `NoFiles` if `ver` has nothing
+    /// to download.
+    pub fn new(info: &ShallowSearchResult, ver: &Version) -> Result<Self, RinthaError> {
+        let mut cmod = ConfigMod {
+            id: info.id.clone(),
+            slug: info.slug.clone(),
+            title: info.title.clone(),
+            author_username: info.author_username.clone(),
+            small_description: info.small_description.clone(),
+            latest_mc_ver: info.latest_mc_ver.clone(),
+            license: info.license.clone(),
+            sha1: String::new(),
+            sha512: String::new(),
+            installed_version_id: ver.id.clone(),
+            installed_version_number: String::new(),
+            installed_version_type: ver.version_type,
+            supported_game_versions: vec![],
+            loaders: vec![],
+            current_filename: String::new(),
+            dependencies: vec![],
+            explicit: true,
+            installed_for: None,
+            pinned: false,
+            disabled: false,
+        };
+        cmod.set_version(ver, String::new(), String::new())?;
+        Ok(cmod)
+    }
+
+    /// Picks the newest version in `versions` that can replace the installed one, that is one
+    /// published after it which supports the same game versions and loaders and isn't less
+    /// stable than what's installed. Mods installed before loaders were recorded take them from
+    /// the installed version in `versions`, without it there's no telling whether a version is
+    /// for the right loader so that fails with `UnknownLoaders`.
+    pub fn find_update<'a>(
+        &self,
+        versions: &'a [Version],
+    ) -> Result<Option<&'a Version>, RinthaError> {
+        let installed = versions
+            .iter()
+            .find(|ver| ver.id.0 == self.installed_version_id.0);
+        let installed_date = installed.map(|ver| ver.date_published.as_str());
+
+        let loaders = match (self.loaders.is_empty(), installed) {
+            (false, _) => &self.loaders,
+            (true, Some(installed)) if !installed.loaders.is_empty() => &installed.loaders,
+            (true, _) => return Err(RinthaError::UnknownLoaders(self.title.clone())),
+        };
+
+        Ok(versions
+            .iter()
+            .filter(|ver| installed_date.is_none_or(|date| ver.date_published.as_str() > date))
+            .filter(|ver| {
+                self.supported_game_versions
+                    .iter()
+                    .all(|gv| ver.game_versions.contains(gv))
+            })
+            .filter(|ver| loaders.iter().all(|l| ver.loaders.contains(l)))
+            .filter(|ver| {
+                release_rank(ver.version_type) >= release_rank(self.installed_version_type)
+            })
+            .max_by(|a, b| a.date_published.cmp(&b.date_published)))
+    }
+
+    /// Points this manifest entry at `ver`, whose primary file hashed to `sha1` and `sha512`.
+    /// Nothing changes if `ver` has no files.
+    pub fn set_version(
+        &mut self,
+        ver: &Version,
+        sha1: String,
+        sha512: String,
+    ) -> Result<(), RinthaError> {
+        let file = ver.files.first().ok_or(RinthaError::NoFiles)?;
+
+        self.installed_version_id = ver.id.clone();
+        self.installed_version_number = ver.version_number.clone();
+        self.installed_version_type = ver.version_type;
+        self.supported_game_versions = ver.game_versions.clone();
+        self.loaders = ver.loaders.clone();
+        self.current_filename = file.filename.clone();
+        self.sha1 = sha1;
+        self.sha512 = sha512;
+        Ok(())
+    }
+}
+
+/// One mod in an install plan, see `resolve_install`.
+#[derive(Debug, Clone)]
+pub struct InstallStep {
+    /// The manifest entry, its hashes are filled in after downloading
+    pub cmod: ConfigMod,
+    pub version: Version,
+    /// Title of the mod that pulled this one in, `None` for the mod that was asked for
+    pub required_by: Option<String>,
+    /// The installed version of the same mod that this step replaces
+    pub replaces: Option<ConfigMod>,
+}
+
+/// Walks the dependencies of `root` recursively and returns everything that has to be
+/// installed for it to work, starting with `root` itself. Modrinth hands dependencies out as
+/// specific version IDs, those exact versions are used if `profile` supports them and the
+/// newest version that it does support otherwise, unless `allow_incompatible` is set. Mods
+/// that are already in `profile` (or earlier in the plan) are skipped.
+pub fn resolve_install(
+    profile: &Profile,
+    info: &ShallowSearchResult,
+    root: &Version,
+    allow_incompatible: bool,
+) -> Result<Vec<InstallStep>, RinthaError> {
+    resolve_dependencies(
+        profile,
+        InstallStep {
+            cmod: ConfigMod::new(info, root)?,
+            version: root.clone(),
+            required_by: None,
+            replaces: None,
+        },
+        allow_incompatible,
+    )
+}
+
+/// Like `resolve_install`, but starts from a step that's already set up, like one that
+/// updates an installed mod. The dependencies of `root` are added to its `cmod` as they're
+/// found, so they should start out empty.
+pub fn resolve_dependencies(
+    profile: &Profile,
+    root: InstallStep,
+    allow_incompatible: bool,
+) -> Result<Vec<InstallStep>, RinthaError> {
+    let mut seen: HashSet<String> = profile
+        .mods
+        .iter()
+        .flatten()
+        .map(|cmod| cmod.id.0.clone())
+        .collect();
+    seen.insert(root.cmod.id.0.clone());
+
+    let mut plan = vec![root];
+
+    // plan indices whose dependencies haven't been looked at yet
+    let mut pending = vec![0];
+    while let Some(idx) = pending.pop() {
+        for dep_id in plan[idx].version.dependencies.clone() {
+            let dep = version_get(dep_id)?;
+            plan[idx].cmod.dependencies.push(dep.mod_id.clone());
+
+            if seen.insert(dep.mod_id.0.clone()) {
+                let dep = if allow_incompatible {
+                    dep
+                } else {
+                    compatible_dependency(profile, dep, &plan[idx].cmod.title)?
+                };
+                let dep_info = version_info(&dep)?;
+                let mut cmod = ConfigMod::new(&dep_info, &dep)?;
+                cmod.explicit = false;
+                cmod.installed_for = Some(plan[idx].cmod.id.clone());
+                plan.push(InstallStep {
+                    cmod,
+                    version: dep,
+                    required_by: Some(plan[idx].cmod.title.clone()),
+                    replaces: None,
+                });
+                pending.push(plan.len() - 1);
+            }
+        }
+    }
+
+    Ok(plan)
+}
+
+/// `dep` if `profile` supports it, otherwise the newest version of the same mod that it does
+/// support. Fails with `IncompatibleDependency` if there's none, `required_by` is the title of
+/// the mod that needs it.
+fn compatible_dependency(
+    profile: &Profile,
+    dep: Version,
+    required_by: &str,
+) -> Result<Version, RinthaError> {
+    if profile.supports(&dep) {
+        return Ok(dep);
+    }
+
+    version_list(dep.mod_id.clone())?
+        .into_iter()
+        .filter(|ver| profile.supports(ver))
+        .max_by(|a, b| a.date_published.cmp(&b.date_published))
+        .map_or_else(
+            || {
+                Err(RinthaError::IncompatibleDependency {
+                    title: mod_get(dep.mod_id.clone())?.title,
+                    required_by: required_by.into(),
+                })
+            },
+            Ok,
+        )
+}
+
+/// Looks a file up on modrinth by its sha1 hash, `None` if modrinth doesn't know it.
+pub fn version_from_hash(sha1: &str) -> Result<Option<Version>, RinthaError> {
+    let request = format!(
+        "{}/api/v1/version_file/{}?algorithm=sha1",
+        femtorinth::API_PREFIX,
+        sha1
+    );
+
+    match ureq::get(request.as_str()).call() {
+        Ok(response) => Ok(Some(response.into_json().map_err(femtorinth::Error::from)?)),
+        Err(ureq::Error::Status(404, _)) => Ok(None),
+        Err(err) => Err(femtorinth::Error::from(err).into()),
+    }
+}
+
+/// The IDs of the mods `ver` depends on.
+pub fn dependency_ids(ver: &Version) -> Result<Vec<ModID>, RinthaError> {
+    let mut ids = vec![];
+    for dep_id in ver.dependencies.iter().cloned() {
+        ids.push(version_get(dep_id)?.mod_id);
+    }
+    Ok(ids)
+}
+
+/// Builds the same info a search would give for the mod that `ver` belongs to.
+pub fn version_info(ver: &Version) -> Result<ShallowSearchResult, RinthaError> {
+    let info = mod_get(ver.mod_id.clone())?;
+    let author = user_get(ver.author_id.clone())?;
+
+    Ok(ShallowSearchResult {
+        id: info.id,
+        slug: info.slug,
+        title: info.title,
+        author_username: author.username,
+        small_description: info.description,
+        downloads: info.downloads,
+        follows: 0, // not part of the full mod data
+        latest_mc_ver: ver.game_versions.last().cloned().unwrap_or_default(),
+        license: info.license.id,
+        categories: info.categories,
+        game_versions: ver.game_versions.clone(),
+        client_side: info.client_side,
+        server_side: info.server_side,
+        date_modified: info.updated,
+    })
+}
+
+/// Whether `ver` supports the given minecraft version and mod loader, `None` matches anything.
+pub fn supports(ver: &Version, game_version: Option<&str>, loader: Option<&str>) -> bool {
+    game_version.is_none_or(|gv| ver.game_versions.iter().any(|v| v == gv))
+        && loader.is_none_or(|l| ver.loaders.iter().any(|v| v.eq_ignore_ascii_case(l)))
+}
+
+/// Orders release types from least to most stable.
+pub fn release_rank(release_type: ModReleaseType) -> u8 {
+    match release_type {
+        ModReleaseType::Alpha => 0,
+        ModReleaseType::Beta => 1,
+        ModReleaseType::Release => 2,
+    }
 }
 
 /*
@@ -127,17 +711,215 @@ impl ModChecking for Vec<ConfigMod> {
 
 #[derive(Debug, Error)]
 pub enum RinthaError {
-    #[error("The hash for the file did not match.")]
-    BadFileHash,
+    #[error(
+        "The {algorithm} hash of the file doesn't match, expected {expected} but got {actual}."
+    )]
+    BadFileHash {
+        algorithm: HashAlgorithm,
+        expected: String,
+        actual: String,
+    },
+    #[error("No hash was published for this file, and require_hashes is set in the config.")]
+    NoPublishedHash,
     #[error("This platform isn't supported by Rintha.")]
     UnsupportedPlatform,
-    #[error("This mod is already installed!")]
+    #[error("This version of the mod is already installed, see `rintha list`.")]
     ModAlreadyInstalled,
+    #[error("{0} is pinned to the installed version, pass --override-pin to replace it anyway or `rintha unpin` it.")]
+    ModPinned(String),
+    #[error("No mods have been installed yet!")]
+    NoModsInstalled,
+    #[error("No installed mod matches '{0}'.")]
+    ModNotInstalled(String),
+    #[error("'{0}' matches {1} installed mods, use the ID of the one you mean.")]
+    AmbiguousMod(String, usize),
+    #[error("{title} is required by {}, pass --force to go ahead anyway.", .dependents.join(", "))]
+    RequiredBy {
+        title: String,
+        dependents: Vec<String>,
+    },
+    #[error("No profile named '{0}' exists.")]
+    ProfileNotFound(String),
+    #[error("A profile named '{0}' already exists.")]
+    ProfileAlreadyExists(String),
+    #[error("'{0}' is not a valid profile name, only letters, digits, '-' and '_' are allowed.")]
+    InvalidProfileName(String),
+    #[error("The profile '{0}' is currently in use, switch to another profile first.")]
+    ProfileInUse(String),
+    #[error("A bisect is going on in this profile, finish it with `rintha bisect reset` first.")]
+    BisectInProgress,
+    #[error("No bisect is going on in this profile, start one with `rintha bisect start`.")]
+    NotBisecting,
+    #[error("There are no enabled mods to bisect.")]
+    NothingToBisect,
+    #[error("'{0}' isn't a supported mod loader, use fabric, forge or quilt.")]
+    UnknownLoader(String),
+    #[error("This version doesn't support the profile's minecraft version or mod loader, pass --allow-incompatible to install it anyway.")]
+    IncompatibleVersion,
+    #[error("{title} is required by {required_by} but has no version for the profile's minecraft version and mod loader, pass --allow-incompatible to install it anyway.")]
+    IncompatibleDependency { title: String, required_by: String },
+    #[error("The mod loaders of {0} weren't recorded and its installed version isn't on Modrinth anymore, so it can't be updated.")]
+    UnknownLoaders(String),
+    #[error("{0} problem(s) with the installed mod files were found.")]
+    Drift(usize),
+    #[error("No manifest exists at '{0}'.")]
+    ManifestNotFound(String),
+    #[error("--manifest needs --mods-dir or --game-dir, the mods directory to check against it.")]
+    ManifestWithoutModDir,
+    #[error("The config_revision in the config isn't a valid revision number.")]
+    BadConfigRevision,
+    #[error(
+        "The config is at revision {0}, which is newer than this version of rintha understands."
+    )]
+    ConfigTooNew(usize),
+    #[error("The config has problems, please fix them by hand:\n  {}", .0.join("\n  "))]
+    InvalidConfig(Vec<String>),
+    #[error("Couldn't read the config: {0}")]
+    ConfigRead(#[from] toml::de::Error),
+    #[error("Couldn't write the config: {0}")]
+    ConfigWrite(#[from] toml::ser::Error),
+    #[error("Couldn't load the config: {0}")]
+    Confy(Box<confy::ConfyError>),
+    #[error("Interrupted, all changes were rolled back.")]
+    Interrupted,
+    #[error("'{0}' isn't one of the listed choices.")]
+    InvalidChoice(String),
+    #[error("'{value}' isn't a valid value for --{arg}.")]
+    InvalidArgument { arg: &'static str, value: String },
+    #[error("Query must be longer than or equal to 3 characters.")]
+    QueryTooShort,
+    #[error("Input is needed but stdin isn't a terminal, pass the answers as flags instead (see --help).")]
+    NotInteractive,
+    #[error("No version matching '{0}' was found.")]
+    VersionNotFound(String),
+    #[error("This version has no files to download.")]
+    NoFiles,
+    #[error("{0} download(s) failed.")]
+    DownloadsFailed(usize),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    // both of these are boxed to keep `RinthaError` itself small
+    #[error("Modrinth API error: {0}")]
+    Api(Box<femtorinth::Error>),
+    #[error("Download error: {0}")]
+    Download(Box<downloader::Error>),
 }
 
-#[derive(Debug, Clone)]
+/// The broad kind of a `RinthaError`, for frontends that react to failures without caring
+/// about every single variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Something the user asked for doesn't make sense
+    InvalidInput,
+    /// A profile, mod or version doesn't exist
+    NotFound,
+    /// Something is already there or in use
+    Conflict,
+    /// Modrinth or a download couldn't be reached
+    Network,
+    /// Modrinth answered with an error or something that couldn't be understood
+    Api,
+    /// A file doesn't match its hash, or has no hash to check
+    Integrity,
+    /// The config can't be read, written or used
+    Config,
+    Io,
+    Unsupported,
+    /// Ctrl-C was pressed and the changes were rolled back
+    Interrupted,
+}
+
+impl ErrorKind {
+    /// A stable name for machine readable output
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::InvalidInput => "invalid_input",
+            ErrorKind::NotFound => "not_found",
+            ErrorKind::Conflict => "conflict",
+            ErrorKind::Network => "network",
+            ErrorKind::Api => "api",
+            ErrorKind::Integrity => "integrity",
+            ErrorKind::Config => "config",
+            ErrorKind::Io => "io",
+            ErrorKind::Unsupported => "unsupported",
+            ErrorKind::Interrupted => "interrupted",
+        }
+    }
+}
+
+impl RinthaError {
+    pub fn kind(&self) -> ErrorKind {
+        use RinthaError::*;
+
+        match self {
+            InvalidProfileName(_)
+            | UnknownLoader(_)
+            | IncompatibleVersion
+            | IncompatibleDependency { .. }
+            | InvalidChoice(_)
+            | InvalidArgument { .. }
+            | AmbiguousMod(..)
+            | QueryTooShort
+            | NotInteractive
+            | ManifestWithoutModDir
+            | NotBisecting
+            | NothingToBisect => ErrorKind::InvalidInput,
+            ModNotInstalled(_) | NoModsInstalled | ProfileNotFound(_) | VersionNotFound(_)
+            | NoFiles | ManifestNotFound(_) | UnknownLoaders(_) => ErrorKind::NotFound,
+            ModAlreadyInstalled
+            | ModPinned(_)
+            | ProfileAlreadyExists(_)
+            | ProfileInUse(_)
+            | RequiredBy { .. }
+            | BisectInProgress => ErrorKind::Conflict,
+            BadFileHash { .. } | NoPublishedHash | Drift(_) => ErrorKind::Integrity,
+            BadConfigRevision | ConfigTooNew(_) | InvalidConfig(_) | ConfigRead(_)
+            | ConfigWrite(_) | Confy(_) => ErrorKind::Config,
+            Api(err) => match **err {
+                femtorinth::Error::NetError(ureq::Error::Transport(_)) => ErrorKind::Network,
+                // modrinth saying the mod or version doesn't exist, see `is_not_found`
+                femtorinth::Error::NetError(ureq::Error::Status(404, _)) => ErrorKind::NotFound,
+                _ => ErrorKind::Api,
+            },
+            Download(_) | DownloadsFailed(_) => ErrorKind::Network,
+            Io(_) => ErrorKind::Io,
+            UnsupportedPlatform => ErrorKind::Unsupported,
+            Interrupted => ErrorKind::Interrupted,
+        }
+    }
+
+    /// Whether modrinth answered that the requested mod or version doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            RinthaError::Api(err)
+                if matches!(**err, femtorinth::Error::NetError(ureq::Error::Status(404, _)))
+        )
+    }
+}
+
+impl From<femtorinth::Error> for RinthaError {
+    fn from(err: femtorinth::Error) -> Self {
+        RinthaError::Api(Box::new(err))
+    }
+}
+
+impl From<confy::ConfyError> for RinthaError {
+    fn from(err: confy::ConfyError) -> Self {
+        RinthaError::Confy(Box::new(err))
+    }
+}
+
+impl From<downloader::Error> for RinthaError {
+    fn from(err: downloader::Error) -> Self {
+        RinthaError::Download(Box::new(err))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ShallowSearchResult {
     pub id: ModID,
+    pub slug: String,
     pub title: String,
     pub author_username: String,
     pub small_description: String,
@@ -145,9 +927,197 @@ pub struct ShallowSearchResult {
     pub follows: usize,
     pub latest_mc_ver: String,
     pub license: String,
+    /// Categories on modrinth, the mod loaders are part of these
+    pub categories: Vec<String>,
+    pub game_versions: Vec<String>,
+    pub client_side: ModSideRequirement,
+    pub server_side: ModSideRequirement,
+    /// When the mod was last changed, in RFC 3339
+    pub date_modified: String,
 }
 
-pub fn mod_dir() -> Result<PathBuf, RinthaError> {
+impl From<femtorinth::data_structures::ModResult> for ShallowSearchResult {
+    fn from(hit: femtorinth::data_structures::ModResult) -> Self {
+        ShallowSearchResult {
+            id: hit.get_clean_id(),
+            slug: hit.slug,
+            title: hit.title,
+            author_username: hit.author,
+            small_description: hit.description,
+            downloads: hit.downloads,
+            follows: hit.follows,
+            latest_mc_ver: hit.latest_version,
+            license: hit.license,
+            categories: hit.categories,
+            game_versions: hit.versions,
+            client_side: hit.client_side,
+            server_side: hit.server_side,
+            date_modified: hit.date_modified,
+        }
+    }
+}
+
+/// How search results are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchSort {
+    #[default]
+    Relevance,
+    Downloads,
+    Follows,
+    Newest,
+    Updated,
+}
+
+impl SearchSort {
+    /// The name modrinth uses for this order
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SearchSort::Relevance => "relevance",
+            SearchSort::Downloads => "downloads",
+            SearchSort::Follows => "follows",
+            SearchSort::Newest => "newest",
+            SearchSort::Updated => "updated",
+        }
+    }
+}
+
+impl FromStr for SearchSort {
+    type Err = RinthaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "relevance" => Ok(SearchSort::Relevance),
+            "downloads" => Ok(SearchSort::Downloads),
+            "follows" => Ok(SearchSort::Follows),
+            "newest" => Ok(SearchSort::Newest),
+            "updated" => Ok(SearchSort::Updated),
+            _ => Err(RinthaError::InvalidArgument {
+                arg: "sort",
+                value: s.into(),
+            }),
+        }
+    }
+}
+
+/// A search on modrinth. Every filter has to match, but a mod only needs one of the given
+/// game versions and loaders. Categories all have to match, empty filters match anything.
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pub query: String,
+    pub categories: Vec<String>,
+    pub game_versions: Vec<String>,
+    pub loaders: Vec<String>,
+    /// "mod" or "modpack"
+    pub project_type: Option<String>,
+    /// "required", "optional" or "unsupported"
+    pub client_side: Option<String>,
+    /// "required", "optional" or "unsupported"
+    pub server_side: Option<String>,
+    /// A license ID like "MIT"
+    pub license: Option<String>,
+    pub sort: SearchSort,
+    /// How many results to skip
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl SearchQuery {
+    pub fn new(query: String) -> Self {
+        SearchQuery {
+            query,
+            categories: vec![],
+            game_versions: vec![],
+            loaders: vec![],
+            project_type: None,
+            client_side: None,
+            server_side: None,
+            license: None,
+            sort: SearchSort::default(),
+            offset: 0,
+            limit: 10,
+        }
+    }
+
+    /// The filters in modrinth's facet syntax, the inner lists are ORed and the outer one ANDed.
+    fn facets(&self) -> Vec<Vec<String>> {
+        let mut facets: Vec<Vec<String>> = self
+            .categories
+            .iter()
+            .map(|category| vec![format!("categories:{}", category)])
+            .collect();
+
+        // loaders are categories as far as modrinth is concerned
+        let any_of = |name: &str, values: &[String]| {
+            values
+                .iter()
+                .map(|value| format!("{}:{}", name, value))
+                .collect::<Vec<String>>()
+        };
+        facets.push(any_of("versions", &self.game_versions));
+        facets.push(any_of("categories", &self.loaders));
+
+        let single = [
+            ("project_type", &self.project_type),
+            ("client_side", &self.client_side),
+            ("server_side", &self.server_side),
+            ("license", &self.license),
+        ];
+        for (name, value) in single {
+            if let Some(value) = value {
+                facets.push(vec![format!("{}:{}", name, value)]);
+            }
+        }
+
+        facets.retain(|facet| !facet.is_empty());
+        facets
+    }
+}
+
+/// One page of search results.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchPage {
+    pub hits: Vec<ShallowSearchResult>,
+    pub offset: usize,
+    pub limit: usize,
+    /// How many mods match the search in total, across all pages
+    pub total_hits: usize,
+}
+
+/// Searches modrinth, the query and filters are sent as they are.
+pub fn search(query: &SearchQuery) -> Result<SearchPage, RinthaError> {
+    let request = format!("{}/api/v1/mod", femtorinth::API_PREFIX);
+    let mut request = ureq::get(request.as_str())
+        .query("query", query.query.as_str())
+        .query("index", query.sort.as_str())
+        .query("offset", query.offset.to_string().as_str())
+        .query("limit", query.limit.to_string().as_str());
+
+    let facets = query.facets();
+    if !facets.is_empty() {
+        // a list of lists of strings always serializes
+        let facets = serde_json::to_string(&facets).unwrap_or_default();
+        request = request.query("facets", facets.as_str());
+    }
+
+    let results: ModSearchResults = request
+        .call()
+        .map_err(femtorinth::Error::from)?
+        .into_json()
+        .map_err(femtorinth::Error::from)?;
+
+    Ok(SearchPage {
+        hits: results
+            .hits
+            .into_iter()
+            .map(ShallowSearchResult::from)
+            .collect(),
+        offset: results.offset,
+        limit: results.limit,
+        total_hits: results.total_hits,
+    })
+}
+
+pub fn default_mod_dir() -> Result<PathBuf, RinthaError> {
     let home = tilde("~");
     let home = Path::new(home.as_ref());
 
@@ -175,44 +1145,317 @@ pub fn profile_dir() -> Result<PathBuf, RinthaError> {
     }
 }
 
+/// Scratch space for downloads that haven't been verified and put in place yet.
+pub fn staging_dir() -> Result<PathBuf, RinthaError> {
+    Ok(profile_dir()?.join(".staging"))
+}
+
+/// A mod file that was downloaded and hashed, see `download_versions`.
+#[derive(Debug)]
+pub struct DownloadedFile {
+    /// Where the file is in the cache
+    pub path: PathBuf,
+    pub sha1: String,
+    pub sha512: String,
+    /// The published hash the file was checked against, `None` if modrinth didn't publish one
+    pub verified: Option<HashAlgorithm>,
+    /// Whether the file was already in the cache and didn't have to be downloaded
+    pub cached: bool,
+}
+
+/// Gets the primary files of `vers` from the cache, downloading the missing ones into
+/// `folder` with up to `parallel_downloads` at a time. Every download is checked against the
+/// strongest hash published on modrinth as soon as it finishes and only added to the cache
+/// if it matches.
+///
+/// The results are in the same order as `vers`, one failed file doesn't stop the others.
+/// The outer error is for problems that stop the whole batch, like a download folder that
+/// can't be created.
+pub fn download_versions(
+    vers: &[&Version],
+    folder: &Path,
+    program_config: &FullConfig,
+) -> Result<Vec<Result<DownloadedFile, RinthaError>>, RinthaError> {
+    let mut results: Vec<Option<Result<DownloadedFile, RinthaError>>> =
+        Vec::with_capacity(vers.len());
+    let mut downloads = vec![];
+    // where each download ends up -> index into `vers`
+    let mut pending: HashMap<PathBuf, usize> = HashMap::new();
+
+    for (n, ver) in vers.iter().enumerate() {
+        let file = match ver.files.first() {
+            Some(file) => file,
+            None => {
+                results.push(Some(Err(RinthaError::NoFiles)));
+                continue;
+            }
+        };
+
+        let published = HashAlgorithm::strongest(&file.hashes);
+        if published.is_none() && program_config.require_hashes {
+            results.push(Some(Err(RinthaError::NoPublishedHash)));
+            continue;
+        }
+
+        if let Some(cached) = cache::lookup_hashes(&file.hashes)? {
+            results.push(Some(Ok(DownloadedFile {
+                sha512: sha512_file(&cached.path)?,
+                path: cached.path,
+                sha1: cached.sha1,
+                verified: published,
+                cached: true,
+            })));
+            continue;
+        }
+
+        let hashes = file.hashes.clone();
+        let verify: downloader::Verify =
+            Arc::new(move |path, _| match check_file(&path, &hashes) {
+                Ok(Some(_)) => Verification::Ok,
+                Ok(None) => Verification::NotVerified,
+                Err(_) => Verification::Failed,
+            });
+
+        // prefixed so two mods with the same file name can't overwrite each other
+        let name = PathBuf::from(format!("{}-{}", n, file.filename));
+        pending.insert(folder.join(&name), n);
+        let mut download = Download::new(file.url.as_str())
+            .file_name(&name)
+            .verify(verify);
+        if !program_config.show_progress {
+            download = download.progress(downloader::progress::Noop::create());
+        }
+        downloads.push(download);
+        results.push(None);
+    }
+
+    if !downloads.is_empty() {
+        fs::create_dir_all(folder)?;
+        let mut downloader = Downloader::builder()
+            .download_folder(folder)
+            .parallel_requests(program_config.parallel_downloads.max(1))
+            .build()?;
+
+        // these come back in the order they finished in
+        for result in downloader.download(&downloads)? {
+            let (path, outcome) = match result {
+                // cache_download checks the file again, which also says what didn't match
+                Ok(summary) | Err(downloader::Error::Verification(summary)) => {
+                    let n = match pending.get(&summary.file_name) {
+                        Some(n) => *n,
+                        None => continue,
+                    };
+                    let outcome = cache_download(&summary.file_name, vers[n]);
+                    if outcome.is_err() {
+                        let _ = fs::remove_file(&summary.file_name);
+                    }
+                    (summary.file_name, outcome)
+                }
+                Err(downloader::Error::Download(summary)) => {
+                    let path = summary.file_name.clone();
+                    let _ = fs::remove_file(&path);
+                    (path, Err(downloader::Error::Download(summary).into()))
+                }
+                Err(downloader::Error::File(summary)) => {
+                    let path = summary.file_name.clone();
+                    (path, Err(downloader::Error::File(summary).into()))
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            if let Some(n) = pending.get(&path) {
+                results[*n] = Some(outcome);
+            }
+        }
+    }
+
+    // the downloader reports on every download, so this is only a safety net
+    Ok(results
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| {
+                Err(RinthaError::Io(std::io::Error::other(
+                    "the download never finished",
+                )))
+            })
+        })
+        .collect())
+}
+
+/// Moves a finished and verified download of `ver` into the cache.
+fn cache_download(path: &Path, ver: &Version) -> Result<DownloadedFile, RinthaError> {
+    let verified = check_file(path, &ver.files[0].hashes)?;
+    let sha1 = sha1_file(path)?;
+    let sha512 = sha512_file(path)?;
+
+    let cached = cache::insert(path, sha1.as_str(), sha512.as_str())?;
+    fs::remove_file(path)?;
+
+    Ok(DownloadedFile {
+        path: cached.path,
+        sha1,
+        sha512,
+        verified,
+        cached: false,
+    })
+}
+
+/// The hashes modrinth publishes for files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Strongest first
+    const ALL: [HashAlgorithm; 2] = [HashAlgorithm::Sha512, HashAlgorithm::Sha1];
+
+    /// The name modrinth uses for it in `hashes`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha512 => "sha512",
+        }
+    }
+
+    pub fn hash_file(self, path: &Path) -> Result<String, RinthaError> {
+        match self {
+            HashAlgorithm::Sha1 => sha1_file(path),
+            HashAlgorithm::Sha512 => sha512_file(path),
+        }
+    }
+
+    /// The strongest algorithm with a hash in `hashes`.
+    pub fn strongest(hashes: &HashMap<String, String>) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| hashes.contains_key(algorithm.as_str()))
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Checks the file at `path` against the strongest of the published `hashes` and returns
+/// which one that was, `None` means there was nothing to check against.
+pub fn check_file(
+    path: &Path,
+    hashes: &HashMap<String, String>,
+) -> Result<Option<HashAlgorithm>, RinthaError> {
+    let algorithm = match HashAlgorithm::strongest(hashes) {
+        Some(algorithm) => algorithm,
+        None => return Ok(None),
+    };
+
+    let expected = hashes[algorithm.as_str()].to_lowercase();
+    let actual = algorithm.hash_file(path)?;
+    if actual == expected {
+        Ok(Some(algorithm))
+    } else {
+        Err(RinthaError::BadFileHash {
+            algorithm,
+            expected,
+            actual,
+        })
+    }
+}
+
+pub fn is_jar(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext == "jar")
+}
+
+pub fn sha1_file(path: &Path) -> Result<String, RinthaError> {
+    let file = fs::read(path)?;
+    Ok(format!("{:x}", sha1::Sha1::digest(&file)))
+}
+
+pub fn sha512_file(path: &Path) -> Result<String, RinthaError> {
+    let file = fs::read(path)?;
+    Ok(format!("{:x}", sha2::Sha512::digest(&file)))
+}
+
+/// Profile names double as directory names inside `profile_dir()`, so they're kept to a
+/// conservative set of characters that can't escape it or clash with `rintha.toml`.
+pub fn check_profile_name(name: &str) -> Result<(), RinthaError> {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        Ok(())
+    } else {
+        Err(RinthaError::InvalidProfileName(name.into()))
+    }
+}
+
+/// Where jars that weren't installed by rintha get moved to when the user asks for it, the
+/// leading dot keeps it from ever clashing with a profile directory.
+pub fn unmanaged_dir() -> Result<PathBuf, RinthaError> {
+    Ok(profile_dir()?.join(".unmanaged"))
+}
+
+/// The first page of results for `query` by relevance, without any filters.
 pub fn shallow_search(
     query: String,
     limit: Option<usize>,
-) -> Result<Vec<ShallowSearchResult>, Box<dyn std::error::Error>> {
-    // FIXME: shite error handling
-    let slimit;
-    if let Some(ok) = limit {
-        slimit = Some(ok + 1);
-    } else {
-        slimit = Some(10 + 1);
-    }
-
-    let results = femtorinth::search_mods(query, None, slimit)?;
-
-    let mut res: Vec<ShallowSearchResult> = vec![];
-    for hit in results.hits {
-        let id = hit.get_clean_id();
-        let title = hit.title.clone();
-        let author_username = hit.author.clone();
-        let small_description = hit.description.clone();
-        let downloads = hit.downloads;
-        let follows = hit.follows;
-        let latest_mc_ver = hit.latest_version.clone();
-        let license = hit.license.clone();
-
-        let ssr = ShallowSearchResult {
-            id,
-            title,
-            author_username,
-            small_description,
-            downloads,
-            follows,
-            latest_mc_ver,
-            license,
-        };
+) -> Result<Vec<ShallowSearchResult>, RinthaError> {
+    let mut search_query = SearchQuery::new(query);
+    search_query.limit = limit.unwrap_or(10) + 1;
 
-        res.push(ssr);
+    Ok(search(&search_query)?.hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::tests::{cmod, version};
+
+    /// A sodium that was installed before loaders were recorded, at version `vSOD`.
+    fn unrecorded_loaders() -> ConfigMod {
+        let mut cmod = cmod("SOD", &[], true);
+        cmod.supported_game_versions = vec!["1.18.1".into()];
+        cmod.loaders = vec![];
+        cmod
+    }
+
+    #[test]
+    fn find_update_takes_loaders_from_the_installed_version() {
+        let versions = [
+            version("SOD", "vSOD", "2022-01-01", &["fabric"]),
+            version("SOD", "forge", "2022-03-01", &["forge"]),
+            version("SOD", "quilt", "2022-04-01", &["quilt"]),
+            version("SOD", "fabric", "2022-02-01", &["fabric"]),
+        ];
+        let found = unrecorded_loaders().find_update(&versions).unwrap();
+        assert_eq!(found.map(|ver| ver.id.0.as_str()), Some("fabric"));
+    }
+
+    #[test]
+    fn find_update_without_any_known_loaders_fails() {
+        let versions = [
+            version("SOD", "forge", "2022-03-01", &["forge"]),
+            version("SOD", "fabric", "2022-02-01", &["fabric"]),
+        ];
+        assert!(matches!(
+            unrecorded_loaders().find_update(&versions),
+            Err(RinthaError::UnknownLoaders(_))
+        ));
     }
 
-    Ok(res)
+    #[test]
+    fn find_update_uses_recorded_loaders() {
+        let mut cmod = unrecorded_loaders();
+        cmod.loaders = vec!["quilt".into()];
+        let versions = [
+            version("SOD", "vSOD", "2022-01-01", &["fabric", "quilt"]),
+            version("SOD", "forge", "2022-03-01", &["forge"]),
+            version("SOD", "quilt", "2022-02-01", &["quilt"]),
+        ];
+        let found = cmod.find_update(&versions).unwrap();
+        assert_eq!(found.map(|ver| ver.id.0.as_str()), Some("quilt"));
+    }
 }
@@ -0,0 +1,482 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml::value::{Table, Value};
+
+use crate::common::{
+    check_profile_name, profile_dir, sha1_file, sha512_file, FullConfig, RinthaError,
+    DEFAULT_PARALLEL_DOWNLOADS,
+};
+
+/// The config layout this version of rintha reads and writes, bump it together with adding a
+/// step to `MIGRATIONS`.
+pub const CONFIG_REVISION: usize = 7;
+
+/// `MIGRATIONS[n]` turns a revision `n` config into a revision `n + 1` one. They're given the
+/// directory the profile directories are in, for the ones that need the installed jars, and
+/// `None` for configs whose jars aren't on this machine.
+const MIGRATIONS: [fn(&mut Table, Option<&Path>); CONFIG_REVISION] = [
+    migrate_0_to_1,
+    migrate_1_to_2,
+    migrate_2_to_3,
+    migrate_3_to_4,
+    migrate_4_to_5,
+    migrate_5_to_6,
+    migrate_6_to_7,
+];
+
+pub fn config_path() -> Result<PathBuf, RinthaError> {
+    Ok(profile_dir()?.join("rintha.toml"))
+}
+
+/// Loads the config, upgrading it from older revisions first (the original file is kept
+/// next to it as a backup) and then checking that it makes sense.
+pub fn load() -> Result<FullConfig, RinthaError> {
+    let path = config_path()?;
+    if !path.exists() {
+        // confy writes out the default config for us
+        return Ok(confy::load("rintha")?);
+    }
+
+    let original = fs::read_to_string(&path)?;
+    let (config, revision) = parse(original.as_str(), Some(profile_dir()?.as_path()))?;
+
+    if revision < CONFIG_REVISION {
+        fs::write(
+            path.with_file_name(format!("rintha.toml.rev{}.bak", revision)),
+            original,
+        )?;
+        store(&config)?;
+    }
+
+    Ok(config)
+}
+
+/// Loads a config or manifest that isn't rintha's own, like one checked into a repository.
+/// It's upgraded and checked the same way as by `load`, but only in memory, the file itself
+/// is never written to. The profile directories on this machine belong to rintha's own
+/// config, so the migrations leave out whatever they'd need those for.
+pub fn load_path(path: &Path) -> Result<FullConfig, RinthaError> {
+    if !path.is_file() {
+        return Err(RinthaError::ManifestNotFound(path.display().to_string()));
+    }
+
+    let (config, _) = parse(fs::read_to_string(path)?.as_str(), None)?;
+    Ok(config)
+}
+
+/// Reads the config in `text`, upgrades it to `CONFIG_REVISION` and validates it. Returns
+/// the revision it was at as well. `profiles` is passed on to the migrations, see
+/// `MIGRATIONS`.
+fn parse(text: &str, profiles: Option<&Path>) -> Result<(FullConfig, usize), RinthaError> {
+    let mut table: Table = toml::from_str(text)?;
+
+    let revision = match table.get("config_revision") {
+        Some(Value::Integer(rev)) if *rev >= 0 => *rev as usize,
+        None => 0,
+        Some(_) => return Err(RinthaError::BadConfigRevision),
+    };
+
+    if revision > CONFIG_REVISION {
+        return Err(RinthaError::ConfigTooNew(revision));
+    }
+
+    for migration in &MIGRATIONS[revision..] {
+        migration(&mut table, profiles);
+    }
+    table.insert(
+        "config_revision".into(),
+        Value::Integer(CONFIG_REVISION as i64),
+    );
+
+    let config: FullConfig = Value::Table(table).try_into()?;
+
+    let problems = validate(&config);
+    if !problems.is_empty() {
+        return Err(RinthaError::InvalidConfig(
+            problems.iter().map(ToString::to_string).collect(),
+        ));
+    }
+
+    Ok((config, revision))
+}
+
+/// Saves the config, the new file is written next to the old one first and then renamed
+/// over it so a crash can't leave a half written config behind.
+pub fn store(config: &FullConfig) -> Result<(), RinthaError> {
+    let path = config_path()?;
+    let partial = path.with_file_name("rintha.toml.partial");
+
+    fs::create_dir_all(profile_dir()?)?;
+    fs::write(&partial, toml::to_string_pretty(config)?)?;
+    fs::rename(partial, path)?;
+
+    Ok(())
+}
+
+/// Something about a config that can't be right, see `validate`.
+#[derive(Debug)]
+pub enum ConfigProblem {
+    MissingCurrentProfile(String),
+    BadProfileName(String),
+    NameMismatch { key: String, name: String },
+    DuplicateModId { profile: String, id: String },
+    DuplicateFilename { profile: String, filename: String },
+    NoParallelDownloads,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigProblem::MissingCurrentProfile(name) => write!(
+                f,
+                "the current profile '{}' doesn't exist in [profiles]",
+                name
+            ),
+            ConfigProblem::BadProfileName(name) => {
+                write!(f, "'{}' can't be used as a profile name", name)
+            }
+            ConfigProblem::NameMismatch { key, name } => write!(
+                f,
+                "the profile under [profiles.{}] is named '{}'",
+                key, name
+            ),
+            ConfigProblem::DuplicateModId { profile, id } => write!(
+                f,
+                "the profile '{}' has the mod ID '{}' more than once",
+                profile, id
+            ),
+            ConfigProblem::DuplicateFilename { profile, filename } => write!(
+                f,
+                "the profile '{}' has the file '{}' more than once",
+                profile, filename
+            ),
+            ConfigProblem::NoParallelDownloads => {
+                write!(f, "parallel_downloads has to be at least 1")
+            }
+        }
+    }
+}
+
+/// Checks the invariants the rest of rintha relies on.
+pub fn validate(config: &FullConfig) -> Vec<ConfigProblem> {
+    let mut problems = vec![];
+
+    if !config
+        .profiles
+        .contains_key(config.current_profile.as_str())
+    {
+        problems.push(ConfigProblem::MissingCurrentProfile(
+            config.current_profile.clone(),
+        ));
+    }
+
+    if config.parallel_downloads == 0 {
+        problems.push(ConfigProblem::NoParallelDownloads);
+    }
+
+    let mut keys: Vec<&String> = config.profiles.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        let profile = &config.profiles[key];
+
+        if check_profile_name(key).is_err() {
+            problems.push(ConfigProblem::BadProfileName(key.clone()));
+        }
+
+        if profile.name != *key {
+            problems.push(ConfigProblem::NameMismatch {
+                key: key.clone(),
+                name: profile.name.clone(),
+            });
+        }
+
+        let mut ids = HashSet::new();
+        let mut filenames = HashSet::new();
+        for cmod in profile.mods.iter().flatten() {
+            if !ids.insert(cmod.id.0.as_str()) {
+                problems.push(ConfigProblem::DuplicateModId {
+                    profile: key.clone(),
+                    id: cmod.id.0.clone(),
+                });
+            }
+
+            if !filenames.insert(cmod.current_filename.as_str()) {
+                problems.push(ConfigProblem::DuplicateFilename {
+                    profile: key.clone(),
+                    filename: cmod.current_filename.clone(),
+                });
+            }
+        }
+    }
+
+    problems
+}
+
+/// Calls `f` on every mod table of every profile.
+fn for_each_mod(table: &mut Table, f: impl Fn(&mut Table)) {
+    if let Some(Value::Table(profiles)) = table.get_mut("profiles") {
+        for (_, profile) in profiles.iter_mut() {
+            if let Some(Value::Array(mods)) = profile.get_mut("mods") {
+                for cmod in mods.iter_mut() {
+                    if let Value::Table(cmod) = cmod {
+                        f(cmod);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Revision 1 records the loaders and dependencies of every installed mod, neither is known
+/// for mods installed before that so they start out empty.
+fn migrate_0_to_1(table: &mut Table, _: Option<&Path>) {
+    for_each_mod(table, |cmod| {
+        for key in ["loaders", "dependencies"] {
+            cmod.entry(key).or_insert_with(|| Value::Array(vec![]));
+        }
+    });
+}
+
+/// Revision 2 adds the `parallel_downloads` setting.
+fn migrate_1_to_2(table: &mut Table, _: Option<&Path>) {
+    table
+        .entry("parallel_downloads")
+        .or_insert_with(|| Value::Integer(DEFAULT_PARALLEL_DOWNLOADS.into()));
+}
+
+/// Adds `key` with `value` to every installed mod of every profile that doesn't have it yet.
+fn add_mod_field(table: &mut Table, key: &str, value: Value) {
+    for_each_mod(table, |cmod| {
+        cmod.entry(key).or_insert_with(|| value.clone());
+    });
+}
+
+/// Revision 3 records the sha512 of every installed mod next to its sha1 and adds the
+/// `require_hashes` setting. The sha512 is taken from the jar in the profile directory if
+/// that still matches the sha1, otherwise (or without `profiles_dir`) it's left empty.
+fn migrate_2_to_3(table: &mut Table, profiles_dir: Option<&Path>) {
+    table
+        .entry("require_hashes")
+        .or_insert(Value::Boolean(false));
+
+    let profiles = match table.get_mut("profiles") {
+        Some(Value::Table(profiles)) => profiles,
+        _ => return,
+    };
+
+    for (name, profile) in profiles.iter_mut() {
+        let dir = profiles_dir.map(|dir| dir.join(name));
+        let mods = match profile.get_mut("mods") {
+            Some(Value::Array(mods)) => mods,
+            _ => continue,
+        };
+
+        for cmod in mods.iter_mut() {
+            if let Value::Table(cmod) = cmod {
+                let sha512 = match (&dir, cmod.get("current_filename"), cmod.get("sha1")) {
+                    (Some(dir), Some(Value::String(filename)), Some(Value::String(sha1))) => {
+                        let path = dir.join(filename);
+                        match sha1_file(&path) {
+                            Ok(actual) if actual == *sha1 => sha512_file(&path).unwrap_or_default(),
+                            _ => String::new(),
+                        }
+                    }
+                    _ => String::new(),
+                };
+                cmod.entry("sha512").or_insert(Value::String(sha512));
+            }
+        }
+    }
+}
+
+/// Revision 4 records the slug of every installed mod. Looking them up would need modrinth,
+/// so they're left empty for the mods that are already installed.
+fn migrate_3_to_4(table: &mut Table, _: Option<&Path>) {
+    add_mod_field(table, "slug", Value::String(String::new()));
+}
+
+/// Revision 5 records whether each mod was installed explicitly or as a dependency. There's
+/// no telling for the mods that are already installed, so they all count as explicit and
+/// `autoremove` leaves them alone.
+fn migrate_4_to_5(table: &mut Table, _: Option<&Path>) {
+    add_mod_field(table, "explicit", Value::Boolean(true));
+}
+
+/// Revision 6 lets mods be pinned, none are to begin with.
+fn migrate_5_to_6(table: &mut Table, _: Option<&Path>) {
+    add_mod_field(table, "pinned", Value::Boolean(false));
+}
+
+/// Revision 7 lets mods be disabled, every installed mod is in the mods directory so they all
+/// start out enabled.
+fn migrate_6_to_7(table: &mut Table, _: Option<&Path>) {
+    add_mod_field(table, "disabled", Value::Boolean(false));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A revision 0 config, from before any of the migrations, with one mod installed.
+    const REV_0: &str = r#"
+current_profile = "default"
+
+[profiles.default]
+name = "default"
+
+[[profiles.default.mods]]
+id = "AANobbMI"
+title = "Sodium"
+author_username = "jellysquid3"
+small_description = ""
+latest_mc_ver = "1.18.1"
+license = "LGPL-3.0"
+sha1 = "0000000000000000000000000000000000000000"
+installed_version_id = "vBAyVrBd"
+installed_version_number = "mc1.18.1-0.4.0"
+installed_version_type = "release"
+supported_game_versions = ["1.18.1"]
+current_filename = "sodium-fabric-mc1.18.1-0.4.0.jar"
+"#;
+
+    fn with_revision(revision: &str) -> String {
+        format!("config_revision = {}\n{}", revision, REV_0)
+    }
+
+    #[test]
+    fn migrates_revision_0_to_current() {
+        let (config, revision) = parse(REV_0, None).unwrap();
+        assert_eq!(revision, 0);
+        assert_eq!(config.config_revision, CONFIG_REVISION);
+        assert_eq!(config.parallel_downloads, DEFAULT_PARALLEL_DOWNLOADS);
+        assert!(!config.require_hashes);
+
+        let cmod = &config.profiles["default"].mods.as_ref().unwrap()[0];
+        assert!(cmod.loaders.is_empty());
+        assert!(cmod.dependencies.is_empty());
+        // the jar isn't there to hash
+        assert_eq!(cmod.sha512, "");
+        assert_eq!(cmod.slug, "");
+        assert!(cmod.explicit);
+        assert!(!cmod.pinned);
+        assert!(!cmod.disabled);
+    }
+
+    #[test]
+    fn migrations_keep_existing_values() {
+        // a revision 1 config that already has its own value for a later setting
+        let text = format!(
+            "parallel_downloads = 9\n{}loaders = [\"fabric\"]\ndependencies = []\n",
+            with_revision("1")
+        );
+
+        let (config, revision) = parse(text.as_str(), None).unwrap();
+        assert_eq!(revision, 1);
+        assert_eq!(config.parallel_downloads, 9);
+        let cmod = &config.profiles["default"].mods.as_ref().unwrap()[0];
+        assert_eq!(cmod.loaders, ["fabric"]);
+    }
+
+    #[test]
+    fn sha512_comes_from_the_profile_jar_only_when_allowed() {
+        let profiles = std::env::temp_dir().join(format!("rintha-migrate-{}", std::process::id()));
+        let jar = profiles
+            .join("default")
+            .join("sodium-fabric-mc1.18.1-0.4.0.jar");
+        fs::create_dir_all(jar.parent().unwrap()).unwrap();
+        fs::write(&jar, "sodium").unwrap();
+        let text = REV_0.replace(
+            "0000000000000000000000000000000000000000",
+            sha1_file(&jar).unwrap().as_str(),
+        );
+        let expected = sha512_file(&jar).unwrap();
+
+        let sha512 = |profiles: Option<&Path>| {
+            let (config, _) = parse(text.as_str(), profiles).unwrap();
+            config.profiles["default"].mods.as_ref().unwrap()[0]
+                .sha512
+                .clone()
+        };
+        let local = sha512(Some(profiles.as_path()));
+        let foreign = sha512(None);
+        fs::remove_dir_all(&profiles).unwrap();
+
+        assert_eq!(local, expected);
+        assert_eq!(foreign, "");
+    }
+
+    #[test]
+    fn rejects_newer_and_bad_revisions() {
+        let too_new = with_revision(&(CONFIG_REVISION + 1).to_string());
+        assert!(matches!(
+            parse(too_new.as_str(), None),
+            Err(RinthaError::ConfigTooNew(revision)) if revision == CONFIG_REVISION + 1
+        ));
+        assert!(matches!(
+            parse(with_revision("-1").as_str(), None),
+            Err(RinthaError::BadConfigRevision)
+        ));
+        assert!(matches!(
+            parse(with_revision("\"7\"").as_str(), None),
+            Err(RinthaError::BadConfigRevision)
+        ));
+    }
+
+    #[test]
+    fn valid_config_has_no_problems() {
+        let (config, _) = parse(REV_0, None).unwrap();
+        assert!(validate(&config).is_empty());
+    }
+
+    #[test]
+    fn validate_finds_every_problem() {
+        let (mut config, _) = parse(REV_0, None).unwrap();
+        config.current_profile = "missing".into();
+        config.parallel_downloads = 0;
+
+        let mut profile = config.profiles["default"].clone();
+        let mods = profile.mods.as_mut().unwrap();
+        mods.push(mods[0].clone());
+        profile.name = "other".into();
+        config.profiles.insert("default".into(), profile);
+
+        let problems: Vec<String> = validate(&config).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            problems,
+            [
+                "the current profile 'missing' doesn't exist in [profiles]",
+                "parallel_downloads has to be at least 1",
+                "the profile under [profiles.default] is named 'other'",
+                "the profile 'default' has the mod ID 'AANobbMI' more than once",
+                "the profile 'default' has the file 'sodium-fabric-mc1.18.1-0.4.0.jar' more than once",
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_rejects_bad_profile_names() {
+        let (mut config, _) = parse(REV_0, None).unwrap();
+        let mut profile = config.profiles["default"].clone();
+        profile.name = "../escape".into();
+        config.profiles.insert("../escape".into(), profile);
+
+        let problems: Vec<String> = validate(&config).iter().map(ToString::to_string).collect();
+        assert_eq!(problems, ["'../escape' can't be used as a profile name"]);
+    }
+
+    #[test]
+    fn parse_reports_invalid_configs() {
+        let text = REV_0.replace(
+            "current_profile = \"default\"",
+            "current_profile = \"gone\"",
+        );
+        assert!(matches!(
+            parse(text.as_str(), None),
+            Err(RinthaError::InvalidConfig(problems)) if problems.len() == 1
+        ));
+    }
+}
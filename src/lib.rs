@@ -0,0 +1,19 @@
+//! Rintha manages Minecraft mods from Modrinth across multiple profiles, the `rintha` binary
+//! is only a frontend for this library.
+//!
+//! Nothing in here prints or exits. Everything returns a `Result` with a `RinthaError`, and
+//! the longer operations in `ops` report what they're doing through `ops::Event`s. Changes
+//! made to a `FullConfig` only live in memory until they're saved with `config::store`, the
+//! operations that touch files do that themselves as part of their transaction.
+
+pub mod cache;
+pub mod common;
+pub mod config;
+pub mod ops;
+pub mod transaction;
+
+pub use common::{
+    search, shallow_search, ConfigMod, ErrorKind, FullConfig, Loader, Profile, RinthaError,
+    SearchPage, SearchQuery, SearchSort, ShallowSearchResult,
+};
+pub use ops::Event;
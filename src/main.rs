@@ -1,79 +1,444 @@
 use bunt::eprintln;
 use clap::{load_yaml, App, ArgMatches};
-use femtorinth::data_structures::ModID;
+use shellexpand::tilde;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
-use crate::common::FullConfig;
+use rintha::common::{ErrorKind, FullConfig, Loader, ModID, RinthaError, SearchQuery};
+use rintha::config;
 
-mod common;
+mod output;
 mod subcommands;
 
+use output::OutputFormat;
+
 pub enum RemoveArg {
-    Guided,
-    ModID(ModID),
-    Unknown,
+    Guided {
+        force: bool,
+    },
+    /// IDs, slugs or title globs
+    Mods {
+        targets: Vec<String>,
+        force: bool,
+        yes: bool,
+    },
+    All {
+        yes: bool,
+    },
+}
+
+pub enum ProfileArg {
+    New {
+        name: String,
+        game_version: Option<String>,
+        loader: Option<Loader>,
+    },
+    Use(String),
+    Rename {
+        from: String,
+        to: String,
+    },
+    Clone {
+        from: String,
+        to: String,
+    },
+    Delete(String),
+    Show(Option<String>),
+    Sync,
+}
+
+pub enum CacheArg {
+    List,
+    Prune,
+    Clear,
+}
+
+pub enum BisectArg {
+    Start,
+    Good,
+    Bad,
+    Reset,
+}
+
+pub struct GetArgs {
+    pub query: String,
+    pub limit: Option<usize>,
+    /// Pick the mod by its slug or ID instead of searching
+    pub exact: bool,
+    /// A version ID, a version number or "latest"
+    pub version: Option<String>,
+    pub game_version: Option<String>,
+    pub loader: Option<String>,
+    /// Allow versions that don't fit the profile's minecraft version or loader
+    pub allow_incompatible: bool,
+    /// Replace the installed version even if it's pinned
+    pub override_pin: bool,
+    pub yes: bool,
 }
 
 pub enum Subcommand {
-    Get(String, Option<usize>),
+    Get(GetArgs),
+    Search(SearchQuery),
     Remove(RemoveArg),
-    List { full: bool },
+    List {
+        full: bool,
+    },
+    Profile(ProfileArg),
+    Cache(CacheArg),
+    Update {
+        mod_id: Option<ModID>,
+        check: bool,
+        yes: bool,
+    },
+    Pin {
+        target: String,
+        version: Option<String>,
+        yes: bool,
+    },
+    Unpin {
+        target: String,
+    },
+    Enable {
+        targets: Vec<String>,
+    },
+    Bisect(BisectArg),
+    Disable {
+        targets: Vec<String>,
+        force: bool,
+    },
+    Verify {
+        manifest: Option<String>,
+    },
+    Repair,
+    Why {
+        target: String,
+    },
+    Autoremove {
+        yes: bool,
+    },
+    Adopt {
+        yes: bool,
+    },
     Unknown,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> ExitCode {
+    // clap prints its own errors and exits with 1 for bad usage, before anything else runs
+    let yaml = load_yaml!("cli.yaml");
+    let app = App::from_yaml(yaml);
+    let matches = app.get_matches();
+
+    // possible values are limited by cli.yaml
+    let format = match global_value(&matches, "output") {
+        Some("json") => OutputFormat::Json,
+        Some("tsv") => OutputFormat::Tsv,
+        _ => OutputFormat::Human,
+    };
+
+    let result = output::init(format).and_then(|_| run(matches));
+    let code = match &result {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{$bold+red}Error:{/$} {[bold]}", err);
+            exit_code(err)
+        }
+    };
+    output::finish(&result, code);
+
+    ExitCode::from(code)
+}
+
+/// The exit code for each kind of failure, these are documented in the README and must stay
+/// the same between releases.
+fn exit_code(err: &RinthaError) -> u8 {
+    match err.kind() {
+        ErrorKind::InvalidInput => 2,
+        ErrorKind::NotFound => 3,
+        ErrorKind::Conflict => 4,
+        ErrorKind::Network => 5,
+        ErrorKind::Api => 6,
+        ErrorKind::Integrity => 7,
+        ErrorKind::Config => 8,
+        ErrorKind::Io => 9,
+        ErrorKind::Unsupported => 10,
+        // what shells report for a process killed by SIGINT
+        ErrorKind::Interrupted => 130,
+    }
+}
+
+fn run(matches: ArgMatches) -> Result<(), RinthaError> {
     /* configuration handling code begin */
-    let mut program_config: FullConfig = confy::load("rintha")?;
-    // TODO: integrity checks for the full config on load
+    let mut program_config: FullConfig = config::load()?;
     /* configuration handling code end */
 
     /* cli interface handling code begin */
-    let yaml = load_yaml!("cli.yaml");
-    let app = App::from_yaml(yaml);
-    let matches = app.get_matches();
+    program_config.mod_dir_override = mod_dir_override(&matches);
+    program_config.show_progress = output::is_human();
 
     let mut command: Subcommand = Subcommand::Unknown;
-    parse_cli(matches, &mut command);
+    parse_cli(matches, &mut command)?;
     let command = command;
 
     match command {
-        Subcommand::Get(query, limit) => subcommands::get(&mut program_config, query, limit)?,
-        Subcommand::Remove(op) => subcommands::remove(&mut program_config, op)?,
-        Subcommand::List { full } => subcommands::list(&mut program_config, full),
-        Subcommand::Unknown => println!("No such subcommand."),
-    }
+        Subcommand::Get(args) => subcommands::get(&mut program_config, args),
+        Subcommand::Search(query) => subcommands::search(&query),
+        Subcommand::Remove(op) => subcommands::remove(&mut program_config, op),
+        Subcommand::List { full } => subcommands::list(&program_config, full),
+        Subcommand::Profile(op) => subcommands::profile(&mut program_config, op),
+        Subcommand::Cache(op) => subcommands::cache(&program_config, op),
+        Subcommand::Update { mod_id, check, yes } => {
+            subcommands::update(&mut program_config, mod_id, check, yes)
+        }
+        Subcommand::Pin {
+            target,
+            version,
+            yes,
+        } => subcommands::pin(&mut program_config, target.as_str(), version, yes),
+        Subcommand::Unpin { target } => subcommands::unpin(&mut program_config, target.as_str()),
+        Subcommand::Enable { targets } => subcommands::enable(&mut program_config, targets),
+        Subcommand::Disable { targets, force } => {
+            subcommands::disable(&mut program_config, targets, force)
+        }
+        Subcommand::Bisect(op) => subcommands::bisect(&mut program_config, op),
+        Subcommand::Verify { manifest } => subcommands::verify(&program_config, manifest),
+        Subcommand::Repair => subcommands::repair(&program_config),
+        Subcommand::Why { target } => subcommands::why(&program_config, target.as_str()),
+        Subcommand::Autoremove { yes } => subcommands::autoremove(&mut program_config, yes),
+        Subcommand::Adopt { yes } => subcommands::adopt(&mut program_config, yes),
+        Subcommand::Unknown => {
+            println!("No such subcommand.");
+            Ok(())
+        }
+    }?;
     /* cli interface handling code end */
 
-    confy::store("rintha", program_config)?;
-    Ok(())
+    config::store(&program_config)
 }
 
-fn parse_cli(matches: ArgMatches, command: &mut Subcommand) {
+/// Global args can be given after the subcommand as well, so the matches of every nested
+/// subcommand get checked too.
+fn global_value<'a>(matches: &'a ArgMatches, arg: &str) -> Option<&'a str> {
+    matches.value_of(arg).or_else(|| {
+        matches
+            .subcommand()
+            .1
+            .and_then(|sub| global_value(sub, arg))
+    })
+}
+
+fn mod_dir_override(matches: &ArgMatches) -> Option<PathBuf> {
+    let value =
+        |arg: &str| global_value(matches, arg).map(|dir| PathBuf::from(tilde(dir).as_ref()));
+
+    value("mods-dir").or_else(|| value("game-dir").map(|dir| dir.join("mods")))
+}
+
+fn parse_cli(matches: ArgMatches, command: &mut Subcommand) -> Result<(), RinthaError> {
     if let Some(submatches) = matches.subcommand_matches("get") {
         if submatches.value_of("query").unwrap().chars().count() >= 3 {
-            *command = Subcommand::Get(
-                submatches.value_of("query").unwrap().to_string(), // value is required
-                match submatches.value_of("limit") {
-                    Some(limit) => Some(limit.parse::<usize>().unwrap()),
-                    None => None,
-                },
-            );
+            let value = |arg: &str| submatches.value_of(arg).map(String::from);
+
+            *command = Subcommand::Get(GetArgs {
+                query: submatches.value_of("query").unwrap().to_string(), // value is required
+                limit: submatches
+                    .value_of("limit")
+                    .map(|limit| {
+                        limit
+                            .parse::<usize>()
+                            .map_err(|_| RinthaError::InvalidArgument {
+                                arg: "limit",
+                                value: limit.into(),
+                            })
+                    })
+                    .transpose()?,
+                exact: submatches.is_present("exact"),
+                version: value("version"),
+                game_version: value("game-version"),
+                loader: value("loader"),
+                allow_incompatible: submatches.is_present("allow-incompatible"),
+                override_pin: submatches.is_present("override-pin"),
+                yes: submatches.is_present("yes"),
+            });
         } else {
-            eprintln!("{$bold+red}Error:{/$} {$bold}Query must be longer than or equal to 3 characters.{/$}");
-            std::process::exit(-1);
+            return Err(RinthaError::QueryTooShort);
         }
+    } else if let Some(submatches) = matches.subcommand_matches("search") {
+        *command = Subcommand::Search(parse_search(submatches)?);
     } else if let Some(submatches) = matches.subcommand_matches("remove") {
-        if submatches.is_present("guided") {
-            *command = Subcommand::Remove(RemoveArg::Guided)
-        } else if submatches.is_present("mod-id") {
-            *command = Subcommand::Remove(RemoveArg::ModID(ModID(
-                submatches.value_of("mod-id").unwrap().into(),
-            )))
-        }
+        let force = submatches.is_present("force");
+        let yes = submatches.is_present("yes");
+        let targets: Vec<String> = ["mods", "mod-id"]
+            .iter()
+            .filter_map(|arg| submatches.values_of(arg))
+            .flatten()
+            .map(String::from)
+            .collect();
+
+        *command = Subcommand::Remove(if submatches.is_present("guided") {
+            RemoveArg::Guided { force }
+        } else if submatches.is_present("all") {
+            RemoveArg::All { yes }
+        } else if !targets.is_empty() {
+            RemoveArg::Mods {
+                targets,
+                force,
+                yes,
+            }
+        } else {
+            // cli.yaml requires one of them
+            return Ok(());
+        });
     } else if let Some(submatches) = matches.subcommand_matches("list") {
         if submatches.is_present("full") {
             *command = Subcommand::List { full: true };
         } else {
             *command = Subcommand::List { full: false };
         }
+    } else if let Some(submatches) = matches.subcommand_matches("update") {
+        *command = Subcommand::Update {
+            mod_id: submatches.value_of("mod-id").map(|id| ModID(id.into())),
+            check: submatches.is_present("check"),
+            yes: submatches.is_present("yes"),
+        };
+    } else if let Some(submatches) = matches.subcommand_matches("verify") {
+        *command = Subcommand::Verify {
+            manifest: submatches.value_of("manifest").map(String::from),
+        };
+    } else if let Some(submatches) = matches.subcommand_matches("pin") {
+        *command = Subcommand::Pin {
+            // value is required
+            target: submatches.value_of("mod").unwrap().into(),
+            version: submatches.value_of("version").map(String::from),
+            yes: submatches.is_present("yes"),
+        };
+    } else if let Some(submatches) = matches.subcommand_matches("unpin") {
+        *command = Subcommand::Unpin {
+            // value is required
+            target: submatches.value_of("mod").unwrap().into(),
+        };
+    } else if let Some(submatches) = matches.subcommand_matches("enable") {
+        *command = Subcommand::Enable {
+            // values are required
+            targets: submatches
+                .values_of("mods")
+                .unwrap()
+                .map(String::from)
+                .collect(),
+        };
+    } else if let Some(submatches) = matches.subcommand_matches("disable") {
+        *command = Subcommand::Disable {
+            // values are required
+            targets: submatches
+                .values_of("mods")
+                .unwrap()
+                .map(String::from)
+                .collect(),
+            force: submatches.is_present("force"),
+        };
+    } else if let Some(submatches) = matches.subcommand_matches("bisect") {
+        *command = Subcommand::Bisect(match submatches.subcommand_name() {
+            Some("start") => BisectArg::Start,
+            Some("good") => BisectArg::Good,
+            Some("bad") => BisectArg::Bad,
+            Some("reset") => BisectArg::Reset,
+            _ => return Ok(()),
+        });
+    } else if matches.subcommand_matches("repair").is_some() {
+        *command = Subcommand::Repair;
+    } else if let Some(submatches) = matches.subcommand_matches("why") {
+        *command = Subcommand::Why {
+            // value is required
+            target: submatches.value_of("mod").unwrap().into(),
+        };
+    } else if let Some(submatches) = matches.subcommand_matches("autoremove") {
+        *command = Subcommand::Autoremove {
+            yes: submatches.is_present("yes"),
+        };
+    } else if let Some(submatches) = matches.subcommand_matches("adopt") {
+        *command = Subcommand::Adopt {
+            yes: submatches.is_present("yes"),
+        };
+    } else if let Some(submatches) = matches.subcommand_matches("cache") {
+        *command = Subcommand::Cache(match submatches.subcommand_name() {
+            Some("list") => CacheArg::List,
+            Some("prune") => CacheArg::Prune,
+            Some("clear") => CacheArg::Clear,
+            _ => return Ok(()),
+        });
+    } else if let Some(submatches) = matches.subcommand_matches("profile") {
+        // all names are required by cli.yaml unless stated otherwise
+        let name = |m: &ArgMatches, arg: &str| m.value_of(arg).unwrap().to_string();
+
+        *command = Subcommand::Profile(match submatches.subcommand() {
+            ("new", Some(m)) => ProfileArg::New {
+                name: name(m, "name"),
+                game_version: m.value_of("game-version").map(String::from),
+                // possible values are limited to valid loaders by cli.yaml
+                loader: m.value_of("loader").map(|l| l.parse().unwrap()),
+            },
+            ("use", Some(m)) => ProfileArg::Use(name(m, "name")),
+            ("rename", Some(m)) => ProfileArg::Rename {
+                from: name(m, "from"),
+                to: name(m, "to"),
+            },
+            ("clone", Some(m)) => ProfileArg::Clone {
+                from: name(m, "from"),
+                to: name(m, "to"),
+            },
+            ("delete", Some(m)) => ProfileArg::Delete(name(m, "name")),
+            ("show", Some(m)) => ProfileArg::Show(m.value_of("name").map(String::from)),
+            ("sync", Some(_)) => ProfileArg::Sync,
+            _ => return Ok(()),
+        });
     }
+
+    Ok(())
+}
+
+fn parse_search(submatches: &ArgMatches) -> Result<SearchQuery, RinthaError> {
+    let number = |arg: &'static str| {
+        submatches
+            .value_of(arg)
+            .map(|value| {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| RinthaError::InvalidArgument {
+                        arg,
+                        value: value.into(),
+                    })
+            })
+            .transpose()
+    };
+    let values = |arg: &str| {
+        submatches
+            .values_of(arg)
+            .map_or(vec![], |values| values.map(String::from).collect())
+    };
+    let value = |arg: &str| submatches.value_of(arg).map(String::from);
+
+    // the query is required and sort and limit have defaults in cli.yaml
+    let mut query = SearchQuery::new(submatches.value_of("query").unwrap().into());
+    query.categories = values("category");
+    query.game_versions = values("game-version");
+    query.loaders = values("loader");
+    query.project_type = value("project-type");
+    query.client_side = value("client-side");
+    query.server_side = value("server-side");
+    query.license = value("license");
+    query.sort = submatches.value_of("sort").unwrap().parse()?;
+    query.limit = number("limit")?.unwrap();
+
+    query.offset = match (number("offset")?, number("page")?) {
+        (Some(offset), _) => offset,
+        (None, Some(0)) => {
+            return Err(RinthaError::InvalidArgument {
+                arg: "page",
+                value: "0".into(),
+            })
+        }
+        (None, Some(page)) => (page - 1) * query.limit,
+        (None, None) => 0,
+    };
+
+    Ok(query)
 }
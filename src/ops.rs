@@ -0,0 +1,246 @@
+//! The things rintha does to profiles and the files they manage, each one either happens
+//! completely or not at all.
+
+pub mod adopt;
+pub mod bisect;
+pub mod deps;
+pub mod install;
+pub mod pin;
+pub mod profile;
+pub mod remove;
+pub mod repair;
+pub mod toggle;
+pub mod update;
+
+use crate::common::{mod_get, ConfigMod, DownloadedFile, ModID, RinthaError};
+
+/// Something that happened during an operation, passed to the callback the operation was
+/// given so frontends can show what's going on.
+#[derive(Debug)]
+pub enum Event<'a> {
+    /// `count` files are about to be downloaded or taken from the cache
+    Downloading { count: usize },
+    /// One file of the batch is done, `name` is the title of the mod it belongs to
+    Downloaded {
+        name: &'a str,
+        result: &'a Result<DownloadedFile, RinthaError>,
+    },
+    /// Everything is downloaded and is being put in place along with the manifest
+    Committing,
+    /// Modrinth is being asked for newer versions of the mod titled `name`
+    Checking { name: &'a str },
+    /// The untracked jar `name` is being looked up on modrinth
+    Identifying { name: &'a str },
+    /// The files of the mod titled `name` are about to be put back
+    Repairing { name: &'a str },
+    /// Something went wrong with the mod titled `name`, the operation carries on without it
+    Failed {
+        name: &'a str,
+        error: &'a RinthaError,
+    },
+}
+
+/// Finds the installed mods `targets` refer to, each target is a mod ID, a slug or a title
+/// where `*` and `?` work like they do in shell globs. Returns their IDs in the order they
+/// are in the profile, a target that matches no mod is an error.
+pub fn select(mods: &[ConfigMod], targets: &[&str]) -> Result<Vec<String>, RinthaError> {
+    let mut selected = vec![false; mods.len()];
+
+    for target in targets {
+        let mut found = false;
+        for (idx, cmod) in mods.iter().enumerate() {
+            if cmod.id.0 == *target
+                || (!cmod.slug.is_empty() && cmod.slug.eq_ignore_ascii_case(target))
+                || glob_match(
+                    target.to_lowercase().as_str(),
+                    cmod.title.to_lowercase().as_str(),
+                )
+            {
+                selected[idx] = true;
+                found = true;
+            }
+        }
+
+        if !found {
+            let idx = lookup_slug(mods, target)?
+                .ok_or_else(|| RinthaError::ModNotInstalled(target.to_string()))?;
+            selected[idx] = true;
+        }
+    }
+
+    Ok(mods
+        .iter()
+        .zip(selected)
+        .filter(|(_, selected)| *selected)
+        .map(|(cmod, _)| cmod.id.0.clone())
+        .collect())
+}
+
+/// Asks modrinth which mod `slug` belongs to, for the mods that were installed before slugs
+/// were recorded. Returns the index of that mod in `mods` if it's installed.
+fn lookup_slug(mods: &[ConfigMod], slug: &str) -> Result<Option<usize>, RinthaError> {
+    if slug.contains(['*', '?']) || mods.iter().all(|cmod| !cmod.slug.is_empty()) {
+        return Ok(None);
+    }
+
+    let info = match mod_get(ModID(slug.into())) {
+        Ok(info) => info,
+        Err(err) => {
+            let err = RinthaError::from(err);
+            return if err.is_not_found() {
+                Ok(None)
+            } else {
+                Err(err)
+            };
+        }
+    };
+
+    Ok(mods.iter().position(|cmod| cmod.id.0 == info.id.0))
+}
+
+/// Matches `text` against `pattern`, where `*` is any number of characters and `?` exactly
+/// one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // where the last `*` was and the text position it's currently standing in for
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // let the `*` swallow one more character and try again
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::common::{ModReleaseType, Version, VersionID};
+    use femtorinth::data_structures::{UserID, VersionFile};
+    use std::collections::HashMap;
+
+    /// An installed mod with the ID `id` that requires the mods with the IDs in `deps`, its
+    /// slug is the lowercase ID and its title the ID followed by " Mod".
+    pub(crate) fn cmod(id: &str, deps: &[&str], explicit: bool) -> ConfigMod {
+        ConfigMod {
+            id: ModID(id.into()),
+            slug: id.to_lowercase(),
+            title: format!("{} Mod", id),
+            author_username: String::new(),
+            small_description: String::new(),
+            latest_mc_ver: String::new(),
+            license: String::new(),
+            sha1: String::new(),
+            sha512: String::new(),
+            installed_version_id: VersionID(format!("v{}", id)),
+            installed_version_number: "1.0".into(),
+            installed_version_type: ModReleaseType::Release,
+            supported_game_versions: vec![],
+            loaders: vec![],
+            current_filename: format!("{}.jar", id.to_lowercase()),
+            dependencies: deps.iter().map(|dep| ModID((*dep).into())).collect(),
+            explicit,
+            installed_for: None,
+            pinned: false,
+            disabled: false,
+        }
+    }
+
+    /// The version `id` of the mod `mod_id` for minecraft 1.18.1, with a single file and no
+    /// dependencies.
+    pub(crate) fn version(mod_id: &str, id: &str, date: &str, loaders: &[&str]) -> Version {
+        Version {
+            id: VersionID(id.into()),
+            mod_id: ModID(mod_id.into()),
+            author_id: UserID(String::new()),
+            featured: false,
+            name: id.into(),
+            version_number: id.into(),
+            changelog: None,
+            changelog_url: None,
+            date_published: date.into(),
+            downloads: 0,
+            version_type: ModReleaseType::Release,
+            files: vec![VersionFile {
+                hashes: HashMap::new(),
+                url: String::new(),
+                filename: format!("{}-{}.jar", mod_id.to_lowercase(), id),
+            }],
+            dependencies: vec![],
+            game_versions: vec!["1.18.1".into()],
+            loaders: loaders.iter().map(|l| (*l).into()).collect(),
+        }
+    }
+
+    #[test]
+    fn glob_matches_literally() {
+        assert!(glob_match("sodium", "sodium"));
+        assert!(!glob_match("sodium", "sodium extra"));
+        assert!(!glob_match("sodium extra", "sodium"));
+        assert!(glob_match("", ""));
+        assert!(!glob_match("", "sodium"));
+    }
+
+    #[test]
+    fn glob_star_matches_any_run() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "fabric api"));
+        assert!(glob_match("fabric*", "fabric api"));
+        assert!(glob_match("*api", "fabric api"));
+        assert!(glob_match("f*c*i", "fabric api"));
+        assert!(glob_match("**api", "fabric api"));
+        assert!(!glob_match("*api", "fabric api extras"));
+        // the `*` has to back off after a partial match
+        assert!(glob_match("*aab", "aaaab"));
+    }
+
+    #[test]
+    fn glob_question_mark_matches_one_char() {
+        assert!(glob_match("lithi?m", "lithium"));
+        assert!(glob_match("???", "mod"));
+        assert!(!glob_match("???", "mods"));
+        assert!(!glob_match("?", ""));
+        assert!(glob_match("?*", "x"));
+    }
+
+    #[test]
+    fn select_by_id_slug_and_title() {
+        let mods = [
+            cmod("AAA", &[], true),
+            cmod("BBB", &[], true),
+            cmod("CCC", &[], true),
+        ];
+
+        assert_eq!(select(&mods, &["BBB"]).unwrap(), ["BBB"]);
+        assert_eq!(select(&mods, &["CCC"]).unwrap(), ["CCC"]);
+        assert_eq!(select(&mods, &["aaa"]).unwrap(), ["AAA"]);
+        // titles are matched case insensitively and results keep the profile order
+        assert_eq!(select(&mods, &["ccc MOD", "a*"]).unwrap(), ["AAA", "CCC"]);
+        assert_eq!(select(&mods, &["*mod"]).unwrap(), ["AAA", "BBB", "CCC"]);
+        assert_eq!(select(&mods, &["AAA", "aaa"]).unwrap(), ["AAA"]);
+    }
+
+    #[test]
+    fn select_fails_on_unmatched_target() {
+        let mods = [cmod("AAA", &[], true)];
+        assert!(matches!(
+            select(&mods, &["AAA", "zzz*"]),
+            Err(RinthaError::ModNotInstalled(target)) if target == "zzz*"
+        ));
+    }
+}
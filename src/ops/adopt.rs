@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+use std::fs;
+
+use crate::cache;
+use crate::common::{
+    dependency_ids, is_jar, sha1_file, sha512_file, version_from_hash, version_info, ConfigMod,
+    FullConfig, RinthaError,
+};
+use crate::ops::Event;
+use crate::transaction::Transaction;
+
+/// What `scan` found in the mods directory.
+#[derive(Debug, Default)]
+pub struct AdoptScan {
+    /// Whether there were any jars the current profile doesn't know about
+    pub untracked: bool,
+    /// Manifest entries for the jars that were identified, `current_filename` is the name
+    /// of the jar
+    pub adoptable: Vec<ConfigMod>,
+    /// File names of the jars modrinth doesn't know
+    pub unidentified: Vec<String>,
+    /// File names of the jars that are another copy of a mod that's installed or adoptable
+    pub duplicates: Vec<String>,
+}
+
+/// Looks up the jars in the mods directory that aren't part of the current profile on
+/// modrinth by their hash.
+pub fn scan(
+    program_config: &FullConfig,
+    on_event: &mut dyn FnMut(Event),
+) -> Result<AdoptScan, RinthaError> {
+    let mod_directory = program_config.mod_dir()?;
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+
+    let known: HashSet<&str> = profile
+        .mods
+        .iter()
+        .flatten()
+        .map(|cmod| cmod.current_filename.as_str())
+        .collect();
+    let installed: HashSet<&str> = profile
+        .mods
+        .iter()
+        .flatten()
+        .map(|cmod| cmod.id.0.as_str())
+        .collect();
+
+    let mut untracked = vec![];
+    if mod_directory.is_dir() {
+        for entry in fs::read_dir(&mod_directory)? {
+            let path = entry?.path();
+            // is_jar already made sure there's a file name
+            if is_jar(&path)
+                && !known.contains(path.file_name().unwrap().to_string_lossy().as_ref())
+            {
+                untracked.push(path);
+            }
+        }
+    }
+    untracked.sort();
+
+    let mut found = AdoptScan {
+        untracked: !untracked.is_empty(),
+        ..AdoptScan::default()
+    };
+    for path in untracked {
+        let filename = path.file_name().unwrap().to_string_lossy().to_string();
+        on_event(Event::Identifying {
+            name: filename.as_str(),
+        });
+
+        let sha1 = sha1_file(&path)?;
+        let sha512 = sha512_file(&path)?;
+        let ver = match version_from_hash(sha1.as_str())? {
+            Some(ver) => ver,
+            None => {
+                found.unidentified.push(filename);
+                continue;
+            }
+        };
+
+        if installed.contains(ver.mod_id.0.as_str())
+            || found.adoptable.iter().any(|cmod| cmod.id.0 == ver.mod_id.0)
+        {
+            found.duplicates.push(filename);
+            continue;
+        }
+
+        let mut cmod = ConfigMod::new(&version_info(&ver)?, &ver)?;
+        cmod.sha1 = sha1;
+        cmod.sha512 = sha512;
+        cmod.current_filename = filename;
+        cmod.dependencies = dependency_ids(&ver)?;
+        found.adoptable.push(cmod);
+    }
+
+    Ok(found)
+}
+
+/// Adds `adoptable` (from `scan`) to the current profile, their jars go into the cache and
+/// from there into the profile directory while the ones in the mods directory stay as they
+/// are. Returns the new manifest entries.
+pub fn adopt(
+    program_config: &mut FullConfig,
+    adoptable: Vec<ConfigMod>,
+) -> Result<Vec<ConfigMod>, RinthaError> {
+    let profname = program_config.current_profile.clone();
+    let profdir = program_config.get_current_prof_path()?;
+    let mod_directory = program_config.mod_dir()?;
+    let mut next = program_config.clone();
+    let profile = next
+        .profiles
+        .get_mut(profname.as_str())
+        .ok_or_else(|| RinthaError::ProfileNotFound(profname.clone()))?;
+
+    let mut tx = Transaction::new()?;
+    for cmod in &adoptable {
+        let filename = cmod.current_filename.as_str();
+        let cached = cache::insert(
+            &mod_directory.join(filename),
+            cmod.sha1.as_str(),
+            cmod.sha512.as_str(),
+        )?;
+        tx.link(&cached.path, profdir.join(filename));
+        profile.add_mod(cmod.clone())?;
+    }
+    tx.commit_swap(program_config, next)?;
+
+    Ok(adoptable)
+}
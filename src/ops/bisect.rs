@@ -0,0 +1,274 @@
+//! Finding the mod that breaks the game the way `git bisect` finds the commit that broke
+//! something. Each step only enables some of the suspects (and whatever they need), and the
+//! suspects shrink depending on whether the game works with them.
+
+use std::collections::HashSet;
+
+use crate::common::{Bisect, ConfigMod, FullConfig, ModID, RinthaError};
+use crate::ops::{deps, toggle};
+
+impl Bisect {
+    /// Whether the suspects can't be narrowed down any further, either because only one is
+    /// left or because they all need each other.
+    pub fn is_done(&self) -> bool {
+        self.suspects.len() <= 1
+            || self
+                .suspects
+                .iter()
+                .all(|suspect| self.enabled.iter().any(|id| id.0 == suspect.0))
+    }
+}
+
+/// Starts a bisect in the current profile, the game is assumed to be broken with the mods
+/// that are enabled now, so they're the suspects. Enables the first half of them.
+pub fn start(program_config: &mut FullConfig) -> Result<Bisect, RinthaError> {
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    if profile.bisect.is_some() {
+        return Err(RinthaError::BisectInProgress);
+    }
+
+    let mods: &[ConfigMod] = profile.mods.as_deref().unwrap_or_default();
+    let suspects: HashSet<usize> = (0..mods.len()).filter(|idx| !mods[*idx].disabled).collect();
+    if suspects.is_empty() {
+        return Err(RinthaError::NothingToBisect);
+    }
+
+    let bisect = Bisect {
+        disabled_before: mods
+            .iter()
+            .filter(|cmod| cmod.disabled)
+            .map(|cmod| cmod.id.clone())
+            .collect(),
+        suspects: ids(mods, &suspects),
+        enabled: ids(mods, &next_step(mods, &suspects)),
+        steps: 0,
+    };
+    write(program_config, bisect)
+}
+
+/// Marks the current step as `good` (the game works) or bad and moves on to the next one.
+/// Nothing changes once the bisect is done.
+pub fn mark(program_config: &mut FullConfig, good: bool) -> Result<Bisect, RinthaError> {
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    let mut bisect = profile.bisect.clone().ok_or(RinthaError::NotBisecting)?;
+    if bisect.is_done() {
+        return Ok(bisect);
+    }
+
+    narrow(
+        profile.mods.as_deref().unwrap_or_default(),
+        &mut bisect,
+        good,
+    );
+    write(program_config, bisect)
+}
+
+/// Ends the bisect, every mod is enabled or disabled again like it was before it started.
+/// Returns the mods whose state changed.
+pub fn reset(program_config: &mut FullConfig) -> Result<Vec<ConfigMod>, RinthaError> {
+    let bisect = program_config
+        .get_profile(program_config.current_profile.as_str())?
+        .bisect
+        .clone()
+        .ok_or(RinthaError::NotBisecting)?;
+
+    toggle::sync_disabled(program_config, None, |_, cmod| {
+        bisect.disabled_before.iter().any(|id| id.0 == cmod.id.0)
+    })
+}
+
+/// Saves `bisect` and enables exactly the mods it says, returns it back.
+fn write(program_config: &mut FullConfig, bisect: Bisect) -> Result<Bisect, RinthaError> {
+    toggle::sync_disabled(program_config, Some(bisect.clone()), |_, cmod| {
+        !bisect.enabled.iter().any(|id| id.0 == cmod.id.0)
+    })?;
+    Ok(bisect)
+}
+
+/// Drops the suspects that the current step of `bisect` rules out and picks the mods for the
+/// next one.
+fn narrow(mods: &[ConfigMod], bisect: &mut Bisect, good: bool) {
+    // mods removed since the bisect started are gone from the suspects as well
+    let suspects = indices(mods, &bisect.suspects);
+    let enabled = indices(mods, &bisect.enabled);
+    let suspects: HashSet<usize> = if good {
+        suspects.difference(&enabled).copied().collect()
+    } else {
+        suspects.intersection(&enabled).copied().collect()
+    };
+
+    bisect.suspects = ids(mods, &suspects);
+    bisect.steps += 1;
+    // with one suspect left it's enabled on its own, so the result can be tried out
+    if !suspects.is_empty() {
+        bisect.enabled = ids(mods, &next_step(mods, &suspects));
+    }
+}
+
+/// The mods to enable for testing about half of `suspects`, along with everything they need
+/// so the game can load them. Suspects that pull in few other suspects go first, so a
+/// library needed by everything doesn't end up in every step.
+fn next_step(mods: &[ConfigMod], suspects: &HashSet<usize>) -> HashSet<usize> {
+    let closure = |idx: usize| deps::required(mods, [idx]);
+    let mut order: Vec<usize> = suspects.iter().copied().collect();
+    order.sort_by_key(|idx| (closure(*idx).intersection(suspects).count(), *idx));
+
+    let half = suspects.len().div_ceil(2);
+    let mut testing: Vec<usize> = vec![];
+    let mut enabled = HashSet::new();
+    for idx in order {
+        if enabled.intersection(suspects).count() >= half {
+            break;
+        }
+
+        testing.push(idx);
+        let next = deps::required(mods, testing.iter().copied());
+        // enabling every suspect at once wouldn't tell anything new
+        if next.intersection(suspects).count() == suspects.len() && suspects.len() > 1 {
+            testing.pop();
+            continue;
+        }
+        enabled = next;
+    }
+
+    // nothing could be split off, the bisect is done with every suspect enabled
+    if enabled.is_empty() {
+        return deps::required(mods, suspects.iter().copied());
+    }
+    enabled
+}
+
+/// The indices of the mods with the IDs in `ids`, IDs that aren't installed anymore are
+/// left out.
+fn indices(mods: &[ConfigMod], ids: &[ModID]) -> HashSet<usize> {
+    (0..mods.len())
+        .filter(|idx| ids.iter().any(|id| id.0 == mods[*idx].id.0))
+        .collect()
+}
+
+/// The IDs of the mods at `indices`, in the order they are in the profile.
+fn ids(mods: &[ConfigMod], indices: &HashSet<usize>) -> Vec<ModID> {
+    (0..mods.len())
+        .filter(|idx| indices.contains(idx))
+        .map(|idx| mods[idx].id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::tests::cmod;
+
+    fn names(ids: &[ModID]) -> Vec<&str> {
+        ids.iter().map(|id| id.0.as_str()).collect()
+    }
+
+    fn all(mods: &[ConfigMod]) -> HashSet<usize> {
+        (0..mods.len()).collect()
+    }
+
+    fn start(mods: &[ConfigMod]) -> Bisect {
+        let suspects = all(mods);
+        Bisect {
+            disabled_before: vec![],
+            suspects: ids(mods, &suspects),
+            enabled: ids(mods, &next_step(mods, &suspects)),
+            steps: 0,
+        }
+    }
+
+    /// Bisects `mods` like someone whose game breaks whenever `culprit` is enabled would,
+    /// returns the remaining suspects and how many steps it took.
+    fn run(mods: &[ConfigMod], culprit: &str) -> (Vec<String>, usize) {
+        let mut bisect = start(mods);
+        while !bisect.is_done() {
+            let good = !bisect.enabled.iter().any(|id| id.0 == culprit);
+            narrow(mods, &mut bisect, good);
+            assert!(bisect.steps <= mods.len(), "the bisect doesn't end");
+        }
+        let suspects = bisect.suspects.into_iter().map(|id| id.0).collect();
+        (suspects, bisect.steps)
+    }
+
+    #[test]
+    fn next_step_enables_half_of_independent_suspects() {
+        let mods: Vec<ConfigMod> = ["A", "B", "C", "D", "E"]
+            .iter()
+            .map(|id| cmod(id, &[], true))
+            .collect();
+        assert_eq!(next_step(&mods, &all(&mods)), HashSet::from([0, 1, 2]));
+        assert_eq!(next_step(&mods, &HashSet::from([3, 4])), HashSet::from([3]));
+    }
+
+    #[test]
+    fn next_step_enables_dependencies_too() {
+        // LIB is needed by everything, so it's tried last instead of dragging in every suspect
+        let mods = [
+            cmod("LIB", &[], false),
+            cmod("A", &["LIB"], true),
+            cmod("B", &["LIB"], true),
+            cmod("C", &["LIB"], true),
+        ];
+        let step = next_step(&mods, &all(&mods));
+        assert!(step.contains(&0));
+        assert_eq!(step.len(), 2);
+    }
+
+    #[test]
+    fn next_step_with_inseparable_suspects_enables_them_all() {
+        let mods = [cmod("A", &["B"], true), cmod("B", &["A"], true)];
+        let step = next_step(&mods, &all(&mods));
+        assert_eq!(step, HashSet::from([0, 1]));
+        assert!(start(&mods).is_done());
+    }
+
+    #[test]
+    fn finds_every_culprit() {
+        let mods = [
+            cmod("FAPI", &[], true),
+            cmod("SOD", &[], true),
+            cmod("LITH", &[], true),
+            cmod("IRIS", &["SOD"], true),
+            cmod("MENU", &["FAPI"], true),
+            cmod("LIB", &[], false),
+            cmod("X", &["LIB"], true),
+        ];
+        for culprit in &mods {
+            let (suspects, steps) = run(&mods, culprit.id.0.as_str());
+            assert_eq!(suspects, [culprit.id.0.as_str()]);
+            assert!(steps <= 4, "{} took {} steps", culprit.id.0, steps);
+        }
+    }
+
+    #[test]
+    fn good_and_bad_narrow_the_suspects() {
+        let mods: Vec<ConfigMod> = ["A", "B", "C", "D"]
+            .iter()
+            .map(|id| cmod(id, &[], true))
+            .collect();
+
+        let mut bad = start(&mods);
+        narrow(&mods, &mut bad, false);
+        assert_eq!(names(&bad.suspects), ["A", "B"]);
+        assert_eq!(bad.steps, 1);
+
+        let mut good = start(&mods);
+        narrow(&mods, &mut good, true);
+        assert_eq!(names(&good.suspects), ["C", "D"]);
+        assert_eq!(names(&good.enabled), ["C"]);
+    }
+
+    #[test]
+    fn removed_mods_leave_the_suspects() {
+        let mut mods: Vec<ConfigMod> = ["A", "B", "C", "D"]
+            .iter()
+            .map(|id| cmod(id, &[], true))
+            .collect();
+        let mut bisect = start(&mods);
+        mods.remove(0);
+
+        narrow(&mods, &mut bisect, false);
+        assert_eq!(names(&bisect.suspects), ["B"]);
+        assert!(bisect.is_done());
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::common::ConfigMod;
+
+/// The mods in `mods` that directly require the mod with the ID `mod_id`.
+pub fn dependents<'a>(mods: &'a [ConfigMod], mod_id: &'a str) -> impl Iterator<Item = usize> + 'a {
+    (0..mods.len()).filter(move |idx| mods[*idx].dependencies.iter().any(|dep| dep.0 == mod_id))
+}
+
+/// Why the mod at `idx` is in `mods`: every chain of requirements leading from it to an
+/// explicitly installed mod, as indices starting with `idx` itself. Only the shortest chain to
+/// each explicit mod is returned, and there are none if nothing needs the mod anymore. An
+/// explicit mod is its own single chain.
+pub fn why(mods: &[ConfigMod], idx: usize) -> Vec<Vec<usize>> {
+    let mut chains = vec![];
+    let mut seen = HashSet::from([idx]);
+    let mut queue = VecDeque::from([vec![idx]]);
+
+    while let Some(chain) = queue.pop_front() {
+        // safe to unwrap, chains always start with `idx`
+        let last = *chain.last().unwrap();
+        if mods[last].explicit {
+            chains.push(chain);
+            continue;
+        }
+
+        for dependent in dependents(mods, mods[last].id.0.as_str()) {
+            if seen.insert(dependent) {
+                let mut next = chain.clone();
+                next.push(dependent);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    chains
+}
+
+/// The indices of every mod that the mods in `roots` need, directly or not, including
+/// `roots` themselves.
+pub fn required(mods: &[ConfigMod], roots: impl IntoIterator<Item = usize>) -> HashSet<usize> {
+    let mut required = HashSet::new();
+    let mut pending: Vec<usize> = roots.into_iter().collect();
+
+    while let Some(idx) = pending.pop() {
+        if !required.insert(idx) {
+            continue;
+        }
+        for dep in &mods[idx].dependencies {
+            if let Some(dep_idx) = mods.iter().position(|cmod| cmod.id.0 == dep.0) {
+                pending.push(dep_idx);
+            }
+        }
+    }
+
+    required
+}
+
+/// The IDs of the mods that were installed as dependencies but aren't needed by any explicit
+/// mod anymore, in the order they are in `mods`.
+pub fn orphans(mods: &[ConfigMod]) -> Vec<String> {
+    let explicit = (0..mods.len()).filter(|idx| mods[*idx].explicit);
+    let needed = required(mods, explicit);
+
+    (0..mods.len())
+        .filter(|idx| !needed.contains(idx))
+        .map(|idx| mods[idx].id.0.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::tests::cmod;
+
+    /// APP needs LIB and UI, UI needs LIB as well, CORE is only needed by LIB. OLD was pulled
+    /// in by something that's gone and TOOL is explicit but needs LIB too.
+    fn profile() -> Vec<ConfigMod> {
+        vec![
+            cmod("CORE", &[], false),
+            cmod("LIB", &["CORE"], false),
+            cmod("UI", &["LIB"], false),
+            cmod("APP", &["LIB", "UI"], true),
+            cmod("OLD", &[], false),
+            cmod("TOOL", &["LIB"], true),
+        ]
+    }
+
+    #[test]
+    fn why_explicit_mod_is_its_own_chain() {
+        assert_eq!(why(&profile(), 3), [vec![3]]);
+    }
+
+    #[test]
+    fn why_finds_shortest_chain_to_each_explicit_mod() {
+        let mods = profile();
+        // CORE -> LIB -> APP, the longer CORE -> LIB -> UI -> APP is left out
+        assert_eq!(why(&mods, 0), [vec![0, 1, 3], vec![0, 1, 5]]);
+        assert_eq!(why(&mods, 2), [vec![2, 3]]);
+    }
+
+    #[test]
+    fn why_orphan_has_no_chains() {
+        assert!(why(&profile(), 4).is_empty());
+    }
+
+    #[test]
+    fn why_survives_dependency_cycles() {
+        let mods = [
+            cmod("A", &["B"], false),
+            cmod("B", &["A"], false),
+            cmod("C", &["B"], true),
+        ];
+        assert_eq!(why(&mods, 0), [vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn required_follows_dependencies() {
+        let mods = profile();
+        assert_eq!(required(&mods, [3]), HashSet::from([0, 1, 2, 3]));
+        assert_eq!(required(&mods, [4]), HashSet::from([4]));
+        // dependencies that aren't installed are skipped
+        let mods = [cmod("A", &["MISSING"], true)];
+        assert_eq!(required(&mods, [0]), HashSet::from([0]));
+    }
+
+    #[test]
+    fn orphans_are_unneeded_dependencies() {
+        assert_eq!(orphans(&profile()), ["OLD"]);
+    }
+
+    #[test]
+    fn removing_the_last_dependent_orphans_the_chain() {
+        let mut mods = profile();
+        mods.retain(|cmod| cmod.id.0 != "APP" && cmod.id.0 != "TOOL");
+        assert_eq!(orphans(&mods), ["CORE", "LIB", "UI", "OLD"]);
+    }
+
+    #[test]
+    fn orphans_keep_dependency_cycles_of_explicit_mods() {
+        let mods = [
+            cmod("A", &["B"], false),
+            cmod("B", &["A"], false),
+            cmod("C", &["A"], true),
+        ];
+        assert!(orphans(&mods).is_empty());
+    }
+}
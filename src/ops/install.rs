@@ -0,0 +1,222 @@
+use crate::common::{
+    download_versions, resolve_install, ConfigMod, FullConfig, InstallStep, RinthaError,
+    ShallowSearchResult, Version,
+};
+use crate::ops::Event;
+use crate::transaction::Transaction;
+
+/// Works out what installing `version` of the mod described by `info` into the profile
+/// `profile` takes, the mod itself comes first and its dependencies after it. The mod counts
+/// as asked for even if it was installed as a dependency before. If another version of the
+/// mod is installed it gets replaced, unless it's pinned and `override_pin` isn't set, the
+/// same version is `promote`d instead. Dependencies are only installed at versions the profile doesn't support with
+/// `allow_incompatible`, see `resolve_install`.
+pub fn plan(
+    program_config: &FullConfig,
+    profile: &str,
+    info: &ShallowSearchResult,
+    version: &Version,
+    override_pin: bool,
+    allow_incompatible: bool,
+) -> Result<Vec<InstallStep>, RinthaError> {
+    let profile = program_config.get_profile(profile)?;
+    let installed = profile
+        .mods
+        .iter()
+        .flatten()
+        .find(|cmod| cmod.id.0 == info.id.0);
+
+    if let Some(installed) = installed {
+        if installed.installed_version_id.0 == version.id.0 {
+            return Err(RinthaError::ModAlreadyInstalled);
+        }
+        if installed.pinned && !override_pin {
+            return Err(RinthaError::ModPinned(installed.title.clone()));
+        }
+    }
+
+    let mut plan = resolve_install(profile, info, version, allow_incompatible)?;
+    if let Some(installed) = installed {
+        // it's still the same mod, so it stays pinned and disabled if it was. It was asked for
+        // this time, so it's explicit from now on even if it was a dependency before
+        let root = &mut plan[0].cmod;
+        root.pinned = installed.pinned;
+        root.disabled = installed.disabled;
+        plan[0].replaces = Some(installed.clone());
+    }
+
+    Ok(plan)
+}
+
+/// Marks the installed mod with the ID `mod_id` in the current profile as asked for, for a
+/// mod that was installed as a dependency and is now `get` at the same version. `autoremove`
+/// leaves it alone from then on. Fails with `ModAlreadyInstalled` if it already was asked for,
+/// returns the new manifest entry otherwise.
+pub fn promote(program_config: &mut FullConfig, mod_id: &str) -> Result<ConfigMod, RinthaError> {
+    let profname = program_config.current_profile.clone();
+    let mut next = program_config.clone();
+    let cmod = next
+        .profiles
+        .get_mut(profname.as_str())
+        .ok_or_else(|| RinthaError::ProfileNotFound(profname.clone()))?
+        .mods
+        .iter_mut()
+        .flatten()
+        .find(|cmod| cmod.id.0 == mod_id)
+        .ok_or_else(|| RinthaError::ModNotInstalled(mod_id.into()))?;
+    if cmod.explicit {
+        return Err(RinthaError::ModAlreadyInstalled);
+    }
+    cmod.explicit = true;
+    cmod.installed_for = None;
+    let cmod = cmod.clone();
+
+    // only the manifest changes, the transaction just saves it
+    Transaction::new()?.commit_swap(program_config, next)?;
+    Ok(cmod)
+}
+
+/// Downloads everything in `plan` and installs it into the current profile and the mods
+/// directory, nothing is installed unless every download succeeds. Returns the new manifest
+/// entries.
+pub fn install(
+    program_config: &mut FullConfig,
+    plan: Vec<InstallStep>,
+    on_event: &mut dyn FnMut(Event),
+) -> Result<Vec<ConfigMod>, RinthaError> {
+    let mut tx = Transaction::new()?;
+
+    on_event(Event::Downloading { count: plan.len() });
+    let versions: Vec<&Version> = plan.iter().map(|step| &step.version).collect();
+    let results = download_versions(&versions, tx.dir(), program_config)?;
+
+    let mut failed = 0;
+    for (step, result) in plan.iter().zip(&results) {
+        if result.is_err() {
+            failed += 1;
+        }
+        on_event(Event::Downloaded {
+            name: step.cmod.title.as_str(),
+            result,
+        });
+    }
+    if failed > 0 {
+        return Err(RinthaError::DownloadsFailed(failed));
+    }
+
+    let mod_directory = program_config.mod_dir()?;
+    let prof_path = program_config.get_current_prof_path()?;
+    let current_prof = program_config.current_profile.clone();
+    let mut next = program_config.clone();
+
+    let mut installed = Vec::with_capacity(plan.len());
+    for (mut step, result) in plan.into_iter().zip(results) {
+        // all of them succeeded, see above
+        let downloaded = result?;
+        step.cmod.sha1 = downloaded.sha1;
+        step.cmod.sha512 = downloaded.sha512;
+
+        let filename = step.cmod.current_filename.as_str();
+        tx.link(&downloaded.path, prof_path.join(filename));
+        if !step.cmod.disabled {
+            tx.place(&downloaded.path, mod_directory.join(filename));
+        }
+
+        let profile = next
+            .profiles
+            .get_mut(current_prof.as_str())
+            .ok_or_else(|| RinthaError::ProfileNotFound(current_prof.clone()))?;
+        installed.push(step.cmod.clone());
+
+        match step.replaces {
+            Some(old) => {
+                if old.current_filename != step.cmod.current_filename {
+                    tx.remove(prof_path.join(old.current_filename.as_str()));
+                    tx.remove(mod_directory.join(old.current_filename.as_str()));
+                }
+                // safe to unwrap, the mod being replaced is in there
+                let mods = profile.mods.as_mut().unwrap();
+                let idx = mods.iter().position(|cmod| cmod.id.0 == old.id.0).unwrap();
+                mods[idx] = step.cmod;
+            }
+            None => profile.add_mod(step.cmod)?,
+        }
+    }
+
+    on_event(Event::Committing);
+    tx.commit_swap(program_config, next)?;
+
+    Ok(installed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{ModID, ModSideRequirement, Profile};
+    use crate::ops::tests::{cmod, version};
+
+    fn info(id: &str) -> ShallowSearchResult {
+        ShallowSearchResult {
+            id: ModID(id.into()),
+            slug: id.to_lowercase(),
+            title: format!("{} Mod", id),
+            author_username: String::new(),
+            small_description: String::new(),
+            downloads: 0,
+            follows: 0,
+            latest_mc_ver: "1.18.1".into(),
+            license: String::new(),
+            categories: vec![],
+            game_versions: vec!["1.18.1".into()],
+            client_side: ModSideRequirement::Required,
+            server_side: ModSideRequirement::Optional,
+            date_modified: String::new(),
+        }
+    }
+
+    /// A config whose current profile has LIB installed as a dependency of APP.
+    fn config(pinned: bool) -> FullConfig {
+        let mut lib = cmod("LIB", &[], false);
+        lib.installed_for = Some(ModID("APP".into()));
+        lib.pinned = pinned;
+        let profile = Profile {
+            mods: Some(vec![lib, cmod("APP", &["LIB"], true)]),
+            ..Profile::default()
+        };
+
+        let mut config = FullConfig::default();
+        config.profiles.insert("default".into(), profile);
+        config
+    }
+
+    #[test]
+    fn getting_a_dependency_makes_it_explicit() {
+        let ver = version("LIB", "vLIB2", "2022-02-01", &["fabric"]);
+        let plan = plan(&config(true), "default", &info("LIB"), &ver, true, false).unwrap();
+
+        assert_eq!(plan.len(), 1);
+        let root = &plan[0].cmod;
+        assert!(root.explicit);
+        assert!(root.installed_for.is_none());
+        assert!(root.pinned);
+        assert!(plan[0].replaces.is_some());
+    }
+
+    #[test]
+    fn same_version_is_already_installed() {
+        let ver = version("LIB", "vLIB", "2022-01-01", &["fabric"]);
+        assert!(matches!(
+            plan(&config(false), "default", &info("LIB"), &ver, false, false),
+            Err(RinthaError::ModAlreadyInstalled)
+        ));
+    }
+
+    #[test]
+    fn pinned_mods_need_override_pin() {
+        let ver = version("LIB", "vLIB2", "2022-02-01", &["fabric"]);
+        assert!(matches!(
+            plan(&config(true), "default", &info("LIB"), &ver, false, false),
+            Err(RinthaError::ModPinned(_))
+        ));
+    }
+}
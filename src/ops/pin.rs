@@ -0,0 +1,28 @@
+use crate::common::{ConfigMod, FullConfig, RinthaError};
+use crate::transaction::Transaction;
+
+/// Pins or unpins the installed mod with the ID `mod_id` in the current profile, pinned mods
+/// are held at their installed version by updates and `get`. Returns the new manifest entry.
+pub fn set_pinned(
+    program_config: &mut FullConfig,
+    mod_id: &str,
+    pinned: bool,
+) -> Result<ConfigMod, RinthaError> {
+    let profname = program_config.current_profile.clone();
+    let mut next = program_config.clone();
+    let cmod = next
+        .profiles
+        .get_mut(profname.as_str())
+        .ok_or_else(|| RinthaError::ProfileNotFound(profname.clone()))?
+        .mods
+        .iter_mut()
+        .flatten()
+        .find(|cmod| cmod.id.0 == mod_id)
+        .ok_or_else(|| RinthaError::ModNotInstalled(mod_id.into()))?;
+    cmod.pinned = pinned;
+    let cmod = cmod.clone();
+
+    // only the manifest changes, the transaction just saves it
+    Transaction::new()?.commit_swap(program_config, next)?;
+    Ok(cmod)
+}
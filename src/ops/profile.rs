@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache;
+use crate::common::{
+    apply_sync, check_profile_name, unmanaged_dir, FullConfig, Loader, Profile, RinthaError,
+    SyncPlan,
+};
+use crate::transaction::Transaction;
+
+/// Makes sure `name` can be used for a brand new profile.
+fn check_new_name(program_config: &FullConfig, name: &str) -> Result<(), RinthaError> {
+    check_profile_name(name)?;
+
+    if program_config.profiles.contains_key(name) {
+        return Err(RinthaError::ProfileAlreadyExists(name.into()));
+    }
+    Ok(())
+}
+
+/// Creates an empty profile and its directory.
+pub fn create(
+    program_config: &mut FullConfig,
+    name: &str,
+    game_version: Option<String>,
+    loader: Option<Loader>,
+) -> Result<(), RinthaError> {
+    check_new_name(program_config, name)?;
+
+    // an empty directory is all there is to it, nothing has to be undone if saving fails
+    fs::create_dir_all(program_config.get_prof_path(name)?)?;
+    let mut next = program_config.clone();
+    next.profiles
+        .insert(name.into(), Profile::new(name, game_version, loader));
+    Transaction::new()?.commit_swap(program_config, next)
+}
+
+/// Makes `name` the current profile and carries out `plan`, which has to come from
+/// `FullConfig::plan_sync` for the same profile. With `move_unmanaged` the jars no profile
+/// knows about are moved out of the mods directory into `unmanaged_dir()` as well.
+pub fn sync(
+    program_config: &mut FullConfig,
+    name: &str,
+    plan: &SyncPlan,
+    move_unmanaged: bool,
+) -> Result<(), RinthaError> {
+    program_config.get_profile(name)?;
+    let mut tx = Transaction::new()?;
+
+    if move_unmanaged {
+        let unmanaged_dir = unmanaged_dir()?;
+        let mut taken = HashSet::new();
+        for path in &plan.unmanaged {
+            if let Some(filename) = path.file_name() {
+                tx.place(path, unmanaged_target(&unmanaged_dir, filename, &mut taken));
+                tx.remove(path.clone());
+            }
+        }
+    }
+
+    apply_sync(plan, &mut tx);
+    let mut next = program_config.clone();
+    next.current_profile = name.into();
+    tx.commit_swap(program_config, next)
+}
+
+/// Where the unmanaged jar `filename` can be moved to in `dir` without replacing a file that's
+/// already there or in `taken`, which are the targets picked so far. Rintha never had those
+/// jars, so a file it replaces would be gone for good once the transaction is done. A taken
+/// name gets a number added before the extension.
+fn unmanaged_target(dir: &Path, filename: &OsStr, taken: &mut HashSet<PathBuf>) -> PathBuf {
+    let name = Path::new(filename);
+    let stem = name.file_stem().unwrap_or(filename).to_string_lossy();
+    let extension = name
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut target = dir.join(filename);
+    let mut n = 1;
+    while target.exists() || taken.contains(&target) {
+        target = dir.join(format!("{}-{}{}", stem, n, extension));
+        n += 1;
+    }
+    taken.insert(target.clone());
+    target
+}
+
+/// Renames the profile `from` to `to` along with its directory.
+pub fn rename(program_config: &mut FullConfig, from: &str, to: &str) -> Result<(), RinthaError> {
+    program_config.get_profile(from)?;
+    check_new_name(program_config, to)?;
+
+    let mut tx = Transaction::new()?;
+    let from_dir = program_config.get_prof_path(from)?;
+    let to_dir = program_config.get_prof_path(to)?;
+    if from_dir.exists() {
+        tx.rename(from_dir, to_dir);
+    } else {
+        fs::create_dir_all(to_dir)?;
+    }
+
+    let mut next = program_config.clone();
+    // safe to unwrap, we already checked that it exists
+    let mut prof = next.profiles.remove(from).unwrap();
+    prof.name = to.into();
+    next.profiles.insert(to.into(), prof);
+
+    if next.current_profile == from {
+        next.current_profile = to.into();
+    }
+
+    tx.commit_swap(program_config, next)
+}
+
+/// Creates the profile `to` with the same mods as `from`, the jars are shared with the cache
+/// where possible.
+pub fn clone(program_config: &mut FullConfig, from: &str, to: &str) -> Result<(), RinthaError> {
+    let mut prof = program_config.get_profile(from)?.clone();
+    check_new_name(program_config, to)?;
+
+    let mut tx = Transaction::new()?;
+    let from_dir = program_config.get_prof_path(from)?;
+    let to_dir = program_config.get_prof_path(to)?;
+    fs::create_dir_all(&to_dir)?;
+
+    for cmod in prof.mods.iter().flatten() {
+        let filename = cmod.current_filename.as_str();
+        let source = match cache::lookup(cmod.sha1.as_str())? {
+            Some(cached) => cached.path,
+            None => from_dir.join(filename),
+        };
+        tx.link(&source, to_dir.join(filename));
+    }
+
+    // the bisect belongs to the profile it was started in, the clone gets the mods enabled
+    // like they were before it started
+    if let Some(bisect) = prof.bisect.take() {
+        for cmod in prof.mods.iter_mut().flatten() {
+            cmod.disabled = bisect.disabled_before.iter().any(|id| id.0 == cmod.id.0);
+        }
+    }
+    prof.name = to.into();
+
+    let mut next = program_config.clone();
+    next.profiles.insert(to.into(), prof);
+    tx.commit_swap(program_config, next)
+}
+
+/// Deletes a profile along with its directory, the current profile can't be deleted.
+pub fn delete(program_config: &mut FullConfig, name: &str) -> Result<(), RinthaError> {
+    program_config.get_profile(name)?;
+
+    if program_config.current_profile == name {
+        return Err(RinthaError::ProfileInUse(name.into()));
+    }
+
+    let mut tx = Transaction::new()?;
+    tx.remove(program_config.get_prof_path(name)?);
+
+    let mut next = program_config.clone();
+    next.profiles.remove(name);
+    tx.commit_swap(program_config, next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unmanaged_target_never_replaces_a_jar() {
+        let dir = std::env::temp_dir().join(format!("rintha-unmanaged-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("mod.jar"), "already moved out before").unwrap();
+        fs::write(dir.join("mod-1.jar"), "and this one too").unwrap();
+
+        let mut taken = HashSet::new();
+        let first = unmanaged_target(&dir, OsStr::new("mod.jar"), &mut taken);
+        let second = unmanaged_target(&dir, OsStr::new("mod.jar"), &mut taken);
+        let other = unmanaged_target(&dir, OsStr::new("other.jar"), &mut taken);
+        let bare = unmanaged_target(&dir, OsStr::new("README"), &mut taken);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(first, dir.join("mod-2.jar"));
+        assert_eq!(second, dir.join("mod-3.jar"));
+        assert_eq!(other, dir.join("other.jar"));
+        assert_eq!(bare, dir.join("README"));
+    }
+}
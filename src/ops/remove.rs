@@ -0,0 +1,70 @@
+use crate::common::{ConfigMod, FullConfig, RinthaError};
+use crate::transaction::Transaction;
+
+/// Fails with `RequiredBy` if a mod that stays after removing `mod_ids` needs one of them.
+pub fn check_dependents(mods: &[ConfigMod], mod_ids: &[&str]) -> Result<(), RinthaError> {
+    for removed in mods
+        .iter()
+        .filter(|cmod| mod_ids.contains(&cmod.id.0.as_str()))
+    {
+        let dependents: Vec<String> = mods
+            .iter()
+            .filter(|cmod| !mod_ids.contains(&cmod.id.0.as_str()))
+            .filter(|cmod| cmod.dependencies.iter().any(|dep| dep.0 == removed.id.0))
+            .map(|cmod| cmod.title.clone())
+            .collect();
+
+        if !dependents.is_empty() {
+            return Err(RinthaError::RequiredBy {
+                title: removed.title.clone(),
+                dependents,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the mods with the IDs in `mod_ids` from the current profile, its directory and the
+/// mods directory all at once, and returns their manifest entries. Nothing is removed if any
+/// of them isn't installed, or if a mod that stays needs one of them and `force` isn't set.
+pub fn remove(
+    program_config: &mut FullConfig,
+    mod_ids: &[&str],
+    force: bool,
+) -> Result<Vec<ConfigMod>, RinthaError> {
+    let profdir = program_config.get_current_prof_path()?;
+    let mod_directory = program_config.mod_dir()?;
+    let profname = program_config.current_profile.clone();
+    let mut next = program_config.clone();
+
+    let profile = next
+        .profiles
+        .get_mut(profname.as_str())
+        .ok_or(RinthaError::ProfileNotFound(profname))?;
+    let mods = profile.mods.as_mut().ok_or(RinthaError::NoModsInstalled)?;
+    if let Some(missing) = mod_ids
+        .iter()
+        .find(|id| !mods.iter().any(|cmod| cmod.id.0 == **id))
+    {
+        return Err(RinthaError::ModNotInstalled(missing.to_string()));
+    }
+
+    if !force {
+        check_dependents(mods, mod_ids)?;
+    }
+
+    let (removed, kept) = mods
+        .drain(..)
+        .partition(|cmod| mod_ids.contains(&cmod.id.0.as_str()));
+    *mods = kept;
+
+    let mut tx = Transaction::new()?;
+    for rmod in &removed {
+        tx.remove(profdir.join(rmod.current_filename.as_str()));
+        tx.remove(mod_directory.join(rmod.current_filename.as_str()));
+    }
+    tx.commit_swap(program_config, next)?;
+
+    Ok(removed)
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::cache;
+use crate::common::{
+    download_versions, version_get, ConfigMod, DriftKind, FullConfig, HashAlgorithm, RinthaError,
+    Version,
+};
+use crate::ops::Event;
+use crate::transaction::Transaction;
+
+/// How `repair` went.
+#[derive(Debug, Default)]
+pub struct RepairOutcome {
+    /// The mods whose files were put back
+    pub repaired: Vec<ConfigMod>,
+    /// Mods whose installed version doesn't exist on modrinth anymore, so there was nothing
+    /// to download
+    pub gone: Vec<ConfigMod>,
+    /// Mods that couldn't be repaired for another reason, each one was reported with
+    /// `Event::Failed` or `Event::Downloaded`
+    pub failed: usize,
+    /// Jars that aren't part of the profile, these are left alone
+    pub extra: usize,
+}
+
+impl RepairOutcome {
+    /// How many broken mods are still broken.
+    pub fn unrepaired(&self) -> usize {
+        self.gone.len() + self.failed
+    }
+}
+
+/// Puts back the missing or modified files of the current profile, from the other copy of
+/// the file if that's still fine, from the cache or else by downloading the installed
+/// version again.
+pub fn repair(
+    program_config: &FullConfig,
+    on_event: &mut dyn FnMut(Event),
+) -> Result<RepairOutcome, RinthaError> {
+    let profname = program_config.current_profile.as_str();
+    let profile = program_config.get_profile(profname)?;
+    let profdir = program_config.get_current_prof_path()?;
+    let mod_directory = program_config.mod_dir()?;
+
+    let mut outcome = RepairOutcome::default();
+    let mut broken: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for problem in program_config.verify_profile(profname)? {
+        match (problem.kind, problem.mod_id) {
+            (DriftKind::Extra, _) | (_, None) => outcome.extra += 1,
+            (_, Some(mod_id)) => broken.entry(mod_id.0).or_default().push(problem.path),
+        }
+    }
+
+    let mut tx = Transaction::new()?;
+    let mut to_download = vec![];
+    for cmod in profile.mods.iter().flatten() {
+        let targets = match broken.get(cmod.id.0.as_str()) {
+            Some(targets) => targets,
+            None => continue,
+        };
+        on_event(Event::Repairing {
+            name: cmod.title.as_str(),
+        });
+
+        // if one of the two copies is still fine there's no need to download anything, disabled
+        // mods only have the one in the profile directory
+        let dirs = if cmod.disabled {
+            vec![&profdir]
+        } else {
+            vec![&profdir, &mod_directory]
+        };
+        let good_copy = dirs
+            .iter()
+            .map(|dir| dir.join(cmod.current_filename.as_str()))
+            .find(|path| !targets.contains(path));
+        if let Some(source) = good_copy {
+            for target in targets {
+                tx.place(&source, target.clone());
+            }
+            outcome.repaired.push(cmod.clone());
+            continue;
+        }
+
+        if let Some(cached) = cache::lookup(cmod.sha1.as_str())? {
+            for target in targets {
+                tx.place(&cached.path, target.clone());
+            }
+            outcome.repaired.push(cmod.clone());
+            continue;
+        }
+
+        let ver = match version_get(cmod.installed_version_id.clone()) {
+            Ok(ver) => ver,
+            Err(err) => {
+                let err = RinthaError::from(err);
+                if err.is_not_found() {
+                    outcome.gone.push(cmod.clone());
+                } else {
+                    on_event(Event::Failed {
+                        name: cmod.title.as_str(),
+                        error: &err,
+                    });
+                    outcome.failed += 1;
+                }
+                continue;
+            }
+        };
+
+        to_download.push((cmod, targets, ver));
+    }
+
+    if !to_download.is_empty() {
+        on_event(Event::Downloading {
+            count: to_download.len(),
+        });
+        let versions: Vec<&Version> = to_download.iter().map(|(_, _, ver)| ver).collect();
+        let results = download_versions(&versions, tx.dir(), program_config)?;
+
+        for ((cmod, targets, _), result) in to_download.iter().zip(results) {
+            on_event(Event::Downloaded {
+                name: cmod.title.as_str(),
+                result: &result,
+            });
+            let downloaded = match result {
+                Ok(downloaded) if downloaded.sha1 == cmod.sha1 => downloaded,
+                Ok(downloaded) => {
+                    on_event(Event::Failed {
+                        name: cmod.title.as_str(),
+                        error: &RinthaError::BadFileHash {
+                            algorithm: HashAlgorithm::Sha1,
+                            expected: cmod.sha1.clone(),
+                            actual: downloaded.sha1,
+                        },
+                    });
+                    outcome.failed += 1;
+                    continue;
+                }
+                Err(_) => {
+                    outcome.failed += 1;
+                    continue;
+                }
+            };
+
+            for target in *targets {
+                tx.place(&downloaded.path, target.clone());
+            }
+            outcome.repaired.push((*cmod).clone());
+        }
+    }
+
+    on_event(Event::Committing);
+    tx.commit(program_config)?;
+
+    Ok(outcome)
+}
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+
+use crate::common::{apply_sync, Bisect, ConfigMod, FullConfig, RinthaError};
+use crate::ops::deps;
+use crate::transaction::Transaction;
+
+/// Takes the mods with the IDs in `mod_ids` out of the mods directory while keeping them in
+/// the profile, and returns the ones that were enabled until now. Nothing changes if an
+/// enabled mod that stays needs one of them and `force` isn't set.
+pub fn disable(
+    program_config: &mut FullConfig,
+    mod_ids: &[&str],
+    force: bool,
+) -> Result<Vec<ConfigMod>, RinthaError> {
+    let mods = current_mods(program_config, mod_ids)?;
+    let targets: HashSet<usize> = (0..mods.len())
+        .filter(|idx| mod_ids.contains(&mods[*idx].id.0.as_str()))
+        .collect();
+
+    if !force {
+        for idx in &targets {
+            let dependents: Vec<String> = deps::dependents(mods, mods[*idx].id.0.as_str())
+                .filter(|dependent| !mods[*dependent].disabled && !targets.contains(dependent))
+                .map(|dependent| mods[dependent].title.clone())
+                .collect();
+
+            if !dependents.is_empty() {
+                return Err(RinthaError::RequiredBy {
+                    title: mods[*idx].title.clone(),
+                    dependents,
+                });
+            }
+        }
+    }
+
+    // toggling is refused during a bisect, so there's none to keep
+    sync_disabled(program_config, None, |idx, cmod| {
+        targets.contains(&idx) || cmod.disabled
+    })
+}
+
+/// Puts the mods with the IDs in `mod_ids` back into the mods directory, along with any
+/// disabled mods they need, and returns the ones that were disabled until now.
+pub fn enable(
+    program_config: &mut FullConfig,
+    mod_ids: &[&str],
+) -> Result<Vec<ConfigMod>, RinthaError> {
+    let mods = current_mods(program_config, mod_ids)?;
+    let targets = deps::required(
+        mods,
+        (0..mods.len()).filter(|idx| mod_ids.contains(&mods[*idx].id.0.as_str())),
+    );
+
+    sync_disabled(program_config, None, |idx, cmod| {
+        !targets.contains(&idx) && cmod.disabled
+    })
+}
+
+/// The mods of the current profile, as long as every ID in `mod_ids` is one of them and no
+/// bisect is going on, since it'd undo the changes on `reset`.
+fn current_mods<'a>(
+    program_config: &'a FullConfig,
+    mod_ids: &[&str],
+) -> Result<&'a [ConfigMod], RinthaError> {
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    if profile.bisect.is_some() {
+        return Err(RinthaError::BisectInProgress);
+    }
+    let mods = profile
+        .mods
+        .as_deref()
+        .ok_or(RinthaError::NoModsInstalled)?;
+
+    if let Some(missing) = mod_ids
+        .iter()
+        .find(|id| !mods.iter().any(|cmod| cmod.id.0 == **id))
+    {
+        return Err(RinthaError::ModNotInstalled(missing.to_string()));
+    }
+    Ok(mods)
+}
+
+/// Disables the mods of the current profile that `disabled` is true for (given their index
+/// and manifest entry), enables the rest and makes the mods directory match. The profile's
+/// bisect is replaced with `bisect` at the same time. Returns the mods whose state changed.
+pub(crate) fn sync_disabled(
+    program_config: &mut FullConfig,
+    bisect: Option<Bisect>,
+    disabled: impl Fn(usize, &ConfigMod) -> bool,
+) -> Result<Vec<ConfigMod>, RinthaError> {
+    let profname = program_config.current_profile.clone();
+    let mut next = program_config.clone();
+    let profile = next
+        .profiles
+        .get_mut(profname.as_str())
+        .ok_or_else(|| RinthaError::ProfileNotFound(profname.clone()))?;
+    profile.bisect = bisect;
+
+    let mut changed = vec![];
+    for (idx, cmod) in profile.mods.iter_mut().flatten().enumerate() {
+        let disabled = disabled(idx, cmod);
+        if cmod.disabled != disabled {
+            cmod.disabled = disabled;
+            changed.push(cmod.clone());
+        }
+    }
+
+    // the same sync switching profiles does, so a stale copy of an enabled mod is fixed too
+    let plan = next.plan_sync(profname.as_str())?;
+    let mut tx = Transaction::new()?;
+    apply_sync(&plan, &mut tx);
+    tx.commit_swap(program_config, next)?;
+
+    Ok(changed)
+}
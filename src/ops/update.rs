@@ -0,0 +1,241 @@
+use std::collections::HashSet;
+
+use crate::common::{
+    download_versions, resolve_dependencies, version_list, ConfigMod, DownloadedFile, FullConfig,
+    InstallStep, ModID, RinthaError, Version,
+};
+use crate::ops::Event;
+use crate::transaction::Transaction;
+
+/// A newer version of an installed mod, see `check`.
+#[derive(Debug, Clone)]
+pub struct Update {
+    /// The mod as it's installed now
+    pub cmod: ConfigMod,
+    pub version: Version,
+    /// The mods `version` depends on
+    pub dependencies: Vec<ModID>,
+    /// The dependencies of `version` that aren't in the profile yet and get installed along
+    /// with it, see `resolve_dependencies`
+    pub installs: Vec<InstallStep>,
+}
+
+/// What `check` found for the current profile.
+#[derive(Debug, Default)]
+pub struct UpdateCheck {
+    pub updates: Vec<Update>,
+    /// Pinned mods, these aren't checked at all
+    pub held: Vec<ConfigMod>,
+    /// Mods that can't be updated safely, see `ConfigMod::find_update`
+    pub not_updatable: Vec<ConfigMod>,
+}
+
+/// How `update` went, the mods whose download failed are left as they were.
+#[derive(Debug, Default)]
+pub struct UpdateOutcome {
+    /// The new manifest entries of the updated mods
+    pub updated: Vec<ConfigMod>,
+    /// The manifest entries of the dependencies that were installed for them
+    pub installed: Vec<ConfigMod>,
+    pub failed: usize,
+}
+
+/// Looks for updates to the mods of the current profile, or only to the mod with the ID
+/// `mod_id`. Only versions that fit the profile count, see `ConfigMod::find_update`.
+pub fn check(
+    program_config: &FullConfig,
+    mod_id: Option<&str>,
+    on_event: &mut dyn FnMut(Event),
+) -> Result<UpdateCheck, RinthaError> {
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    let mods = match profile.mods.as_ref() {
+        Some(mods) if !mods.is_empty() => mods,
+        _ => return Err(RinthaError::NoModsInstalled),
+    };
+
+    if let Some(mod_id) = mod_id {
+        if !mods.iter().any(|cmod| cmod.id.0 == mod_id) {
+            return Err(RinthaError::ModNotInstalled(mod_id.into()));
+        }
+    }
+
+    let mut found = UpdateCheck::default();
+    for cmod in mods {
+        if mod_id.is_some_and(|id| id != cmod.id.0) {
+            continue;
+        }
+        // pinned mods aren't even checked, `rintha unpin` lets them be updated again
+        if cmod.pinned {
+            found.held.push(cmod.clone());
+            continue;
+        }
+
+        on_event(Event::Checking {
+            name: cmod.title.as_str(),
+        });
+        // the installed version stays in so find_update knows what counts as newer
+        let versions: Vec<Version> = version_list(cmod.id.clone())?
+            .into_iter()
+            .filter(|ver| ver.id.0 == cmod.installed_version_id.0 || profile.supports(ver))
+            .collect();
+        let ver = match cmod.find_update(&versions) {
+            Ok(Some(ver)) => ver,
+            Ok(None) => continue,
+            Err(RinthaError::UnknownLoaders(_)) => {
+                found.not_updatable.push(cmod.clone());
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        // the new version might depend on different mods than the installed one
+        let mut root = cmod.clone();
+        root.dependencies = vec![];
+        let mut installs = resolve_dependencies(
+            profile,
+            InstallStep {
+                cmod: root,
+                version: ver.clone(),
+                required_by: None,
+                replaces: Some(cmod.clone()),
+            },
+            false,
+        )?;
+        let root = installs.remove(0);
+        found.updates.push(Update {
+            cmod: cmod.clone(),
+            version: root.version,
+            dependencies: root.cmod.dependencies,
+            installs,
+        });
+    }
+
+    Ok(found)
+}
+
+/// Downloads and installs `updates` into the current profile and the mods directory, along
+/// with the dependencies they need that aren't installed yet. Failed downloads are skipped
+/// and counted, an update is only skipped if its own file or one of its new dependencies
+/// couldn't be downloaded and the other mods are still updated.
+pub fn update(
+    program_config: &mut FullConfig,
+    updates: Vec<Update>,
+    on_event: &mut dyn FnMut(Event),
+) -> Result<UpdateOutcome, RinthaError> {
+    let profdir = program_config.get_current_prof_path()?;
+    let mod_directory = program_config.mod_dir()?;
+    let profname = program_config.current_profile.clone();
+    let mut tx = Transaction::new()?;
+
+    // a dependency that more than one update needs is only downloaded once
+    let mut installs: Vec<&InstallStep> = vec![];
+    for step in updates.iter().flat_map(|update| &update.installs) {
+        if !installs
+            .iter()
+            .any(|other| other.cmod.id.0 == step.cmod.id.0)
+        {
+            installs.push(step);
+        }
+    }
+
+    let versions: Vec<&Version> = updates
+        .iter()
+        .map(|update| &update.version)
+        .chain(installs.iter().map(|step| &step.version))
+        .collect();
+    let names = updates
+        .iter()
+        .map(|update| &update.cmod.title)
+        .chain(installs.iter().map(|step| &step.cmod.title));
+    on_event(Event::Downloading {
+        count: versions.len(),
+    });
+    let results = download_versions(&versions, tx.dir(), program_config)?;
+
+    let mut outcome = UpdateOutcome::default();
+    for (name, result) in names.zip(&results) {
+        if result.is_err() {
+            outcome.failed += 1;
+        }
+        on_event(Event::Downloaded {
+            name: name.as_str(),
+            result,
+        });
+    }
+    let (update_results, install_results) = results.split_at(updates.len());
+
+    let mut next = program_config.clone();
+    let profile = next
+        .profiles
+        .get_mut(profname.as_str())
+        .ok_or_else(|| RinthaError::ProfileNotFound(profname.clone()))?;
+
+    let mut added: HashSet<String> = HashSet::new();
+    for (update, result) in updates.iter().zip(update_results) {
+        let downloaded = match result {
+            Ok(downloaded) => downloaded,
+            Err(_) => continue,
+        };
+        // without all of its new dependencies the updated mod wouldn't work
+        let deps: Option<Vec<(&InstallStep, &DownloadedFile)>> = update
+            .installs
+            .iter()
+            .map(|step| {
+                let idx = installs
+                    .iter()
+                    .position(|other| other.cmod.id.0 == step.cmod.id.0)?;
+                install_results[idx]
+                    .as_ref()
+                    .ok()
+                    .map(|downloaded| (installs[idx], downloaded))
+            })
+            .collect();
+        let deps = match deps {
+            Some(deps) => deps,
+            None => continue,
+        };
+
+        let cmod = profile
+            .mods
+            .iter_mut()
+            .flatten()
+            .find(|cmod| cmod.id.0 == update.cmod.id.0)
+            .ok_or_else(|| RinthaError::ModNotInstalled(update.cmod.id.0.clone()))?;
+
+        let old_filename = cmod.current_filename.clone();
+        cmod.set_version(
+            &update.version,
+            downloaded.sha1.clone(),
+            downloaded.sha512.clone(),
+        )?;
+        cmod.dependencies = update.dependencies.clone();
+        let new_filename = cmod.current_filename.as_str();
+        tx.link(&downloaded.path, profdir.join(new_filename));
+        if !cmod.disabled {
+            tx.place(&downloaded.path, mod_directory.join(new_filename));
+        }
+        if old_filename != new_filename {
+            tx.remove(profdir.join(old_filename.as_str()));
+            tx.remove(mod_directory.join(old_filename.as_str()));
+        }
+        outcome.updated.push(cmod.clone());
+
+        for (step, downloaded) in deps {
+            if !added.insert(step.cmod.id.0.clone()) {
+                continue;
+            }
+            let mut dep = step.cmod.clone();
+            dep.sha1 = downloaded.sha1.clone();
+            dep.sha512 = downloaded.sha512.clone();
+            let filename = dep.current_filename.as_str();
+            tx.link(&downloaded.path, profdir.join(filename));
+            tx.place(&downloaded.path, mod_directory.join(filename));
+            outcome.installed.push(dep.clone());
+            profile.add_mod(dep)?;
+        }
+    }
+
+    on_event(Event::Committing);
+    tx.commit_swap(program_config, next)?;
+
+    Ok(outcome)
+}
@@ -0,0 +1,224 @@
+use rintha::common::{ConfigMod, RinthaError, ShallowSearchResult};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::Write;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Colored text meant for people
+    Human,
+    /// A single JSON document, `{"ok": true, "data": ...}` or
+    /// `{"ok": false, "data": ..., "error": {"kind": ..., "code": ..., "message": ...}}`
+    Json,
+    /// One tab separated row per record, after a header row
+    Tsv,
+}
+
+/// What the current command wants to report, kept until the command is done so the JSON
+/// document can include the error as well.
+struct Machine {
+    format: OutputFormat,
+    /// Where stdout pointed before it was sent to stderr
+    stdout: Box<dyn Write + Send>,
+    data: Value,
+    rows: Vec<Vec<String>>,
+    columns: &'static [&'static str],
+}
+
+static MACHINE: Mutex<Option<Machine>> = Mutex::new(None);
+
+/// Something that can be reported by a command, it's serialized as is for JSON and turned
+/// into `fields` (in the order of `COLUMNS`) for TSV.
+pub trait Record: Serialize {
+    const COLUMNS: &'static [&'static str];
+
+    fn fields(&self) -> Vec<String>;
+}
+
+/// Sets up `format` for the rest of the process. For the machine readable formats colors are
+/// turned off and everything meant for people is sent to stderr, so stdout only ever holds the
+/// report.
+pub fn init(format: OutputFormat) -> Result<(), RinthaError> {
+    if format == OutputFormat::Human {
+        return Ok(());
+    }
+
+    // bunt (through termcolor) checks this before every colored write
+    std::env::set_var("NO_COLOR", "1");
+
+    let stdout = redirect_stdout()?;
+    *lock() = Some(Machine {
+        format,
+        stdout,
+        data: Value::Null,
+        rows: vec![],
+        columns: &[],
+    });
+    Ok(())
+}
+
+#[cfg(unix)]
+fn redirect_stdout() -> Result<Box<dyn Write + Send>, RinthaError> {
+    use std::os::unix::io::FromRawFd;
+
+    // SAFETY: only file descriptors owned by this process are duplicated, and the copy of
+    // stdout is handed to a `File` that's its only owner
+    unsafe {
+        let fd = libc::dup(libc::STDOUT_FILENO);
+        if fd < 0 || libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(Box::new(File::from_raw_fd(fd)))
+    }
+}
+
+/// Without dup2 the text stays on stdout, only the colors are gone.
+#[cfg(not(unix))]
+fn redirect_stdout() -> Result<Box<dyn Write + Send>, RinthaError> {
+    Ok(Box::new(std::io::stdout()))
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<Machine>> {
+    // nothing panics while holding the lock, but the data is still fine if something did
+    MACHINE.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Whether output is meant for people, which is also when progress bars are shown.
+pub fn is_human() -> bool {
+    lock().is_none()
+}
+
+/// Reports `records` as the result of the command, does nothing for human output since
+/// commands print their own text then.
+pub fn records<R: Record>(records: &[R]) {
+    if let Some(machine) = lock().as_mut() {
+        // all the records are plain data, serializing them can't fail
+        machine.data = serde_json::to_value(records).unwrap_or(Value::Null);
+        machine.rows = records.iter().map(Record::fields).collect();
+        machine.columns = R::COLUMNS;
+    }
+}
+
+/// Like `records`, for commands that report exactly one thing.
+pub fn record<R: Record>(record: &R) {
+    if let Some(machine) = lock().as_mut() {
+        machine.data = serde_json::to_value(record).unwrap_or(Value::Null);
+        machine.rows = vec![record.fields()];
+        machine.columns = R::COLUMNS;
+    }
+}
+
+/// Writes out what the command reported along with how it ended, `code` is the exit code of
+/// `result`. Errors are still printed to stderr by the caller for TSV.
+pub fn finish(result: &Result<(), RinthaError>, code: u8) {
+    let mut guard = lock();
+    let machine = match guard.as_mut() {
+        Some(machine) => machine,
+        None => return,
+    };
+
+    let mut out = String::new();
+    match machine.format {
+        OutputFormat::Human => {}
+        OutputFormat::Json => {
+            let mut doc = json!({
+                "ok": result.is_ok(),
+                "data": machine.data.take(),
+            });
+            if let Err(err) = result {
+                doc["error"] = json!({
+                    "kind": err.kind().as_str(),
+                    "code": code,
+                    "message": err.to_string(),
+                });
+            }
+            out = format!("{}\n", doc);
+        }
+        OutputFormat::Tsv => {
+            if !machine.columns.is_empty() {
+                out.push_str(&machine.columns.join("\t"));
+                out.push('\n');
+            }
+            for row in &machine.rows {
+                let row: Vec<String> = row.iter().map(|field| tsv_escape(field)).collect();
+                out.push_str(&row.join("\t"));
+                out.push('\n');
+            }
+        }
+    }
+
+    // stdout being closed isn't worth a second error
+    let _ = machine.stdout.write_all(out.as_bytes());
+    let _ = machine.stdout.flush();
+}
+
+/// Tabs and newlines would break up the row, so they become spaces.
+fn tsv_escape(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+impl Record for ConfigMod {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "author",
+        "version_number",
+        "version_id",
+        "version_type",
+        "filename",
+        "sha1",
+        "explicit",
+        "pinned",
+        "disabled",
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.id.0.clone(),
+            self.title.clone(),
+            self.author_username.clone(),
+            self.installed_version_number.clone(),
+            self.installed_version_id.0.clone(),
+            format!("{:?}", self.installed_version_type).to_lowercase(),
+            self.current_filename.clone(),
+            self.sha1.clone(),
+            self.explicit.to_string(),
+            self.pinned.to_string(),
+            self.disabled.to_string(),
+        ]
+    }
+}
+
+impl Record for ShallowSearchResult {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "slug",
+        "title",
+        "author",
+        "downloads",
+        "follows",
+        "latest_mc_version",
+        "license",
+        "categories",
+        "date_modified",
+        "description",
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.id.0.clone(),
+            self.slug.clone(),
+            self.title.clone(),
+            self.author_username.clone(),
+            self.downloads.to_string(),
+            self.follows.to_string(),
+            self.latest_mc_ver.clone(),
+            self.license.clone(),
+            self.categories.join(","),
+            self.date_modified.clone(),
+            self.small_description.clone(),
+        ]
+    }
+}
@@ -1,6 +1,196 @@
 mod get;
 pub use get::get;
+mod search;
+pub use search::search;
 mod remove;
-pub use remove::remove;
+pub use remove::{autoremove, remove};
+mod why;
+pub use why::why;
 mod list;
 pub use list::list;
+mod profile;
+pub use profile::profile;
+mod update;
+pub use update::update;
+mod pin;
+pub use pin::{pin, unpin};
+mod enable;
+pub use enable::{disable, enable};
+mod bisect;
+pub use bisect::bisect;
+mod verify;
+pub use verify::verify;
+mod repair;
+pub use repair::repair;
+mod adopt;
+pub use adopt::adopt;
+mod cache;
+pub use cache::cache;
+
+use crate::output;
+use bunt::{print, println};
+use rintha::common::{DownloadedFile, FullConfig, InstallStep, RinthaError};
+use rintha::ops::{install, Event};
+use std::io::{IsTerminal, Write};
+
+/// Makes sure there's someone to answer a prompt, so scripts fail instead of hanging.
+pub(crate) fn ensure_interactive() -> Result<(), RinthaError> {
+    if std::io::stdin().is_terminal() {
+        Ok(())
+    } else {
+        Err(RinthaError::NotInteractive)
+    }
+}
+
+/// Asks a yes/no question on stdin, an empty answer picks `default`.
+pub(crate) fn confirm(question: &str, default: bool) -> Result<bool, RinthaError> {
+    ensure_interactive()?;
+
+    if default {
+        print!("{$bold}{} [Y/n]: {/$}", question);
+    } else {
+        print!("{$bold}{} [y/N]: {/$}", question);
+    }
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    Ok(match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    })
+}
+
+/// Prints `prompt` and reads a line from stdin, without the surrounding whitespace.
+pub(crate) fn read_line(prompt: &str) -> Result<String, RinthaError> {
+    ensure_interactive()?;
+
+    // FIXME: get a proper line reader
+    print!("{$bold}{}: {/$}", prompt);
+    std::io::stdout().flush()?;
+    let mut string = String::new();
+    std::io::stdin().read_line(&mut string)?;
+    Ok(string.trim().into())
+}
+
+/// Reads the index of one of the `count` listed items from stdin.
+pub(crate) fn read_choice(count: usize) -> Result<usize, RinthaError> {
+    let choice = read_line("Enter your choice")?;
+    match choice.parse() {
+        Ok(n) if n < count => Ok(n),
+        _ => Err(RinthaError::InvalidChoice(choice)),
+    }
+}
+
+/// Prints how each download of a batch went as they're reported, and a summary at the end.
+#[derive(Default)]
+pub(crate) struct DownloadReport {
+    succeeded: usize,
+    failed: usize,
+}
+
+impl DownloadReport {
+    /// `name` is the title of the mod the file belongs to.
+    pub(crate) fn add(&mut self, name: &str, result: &Result<DownloadedFile, RinthaError>) {
+        match result {
+            Ok(file) if file.cached => {
+                println!("  {[bold+yellow]}: {$green}taken from the cache{/$}", name)
+            }
+            Ok(DownloadedFile {
+                verified: Some(algorithm),
+                ..
+            }) => println!(
+                "  {[bold+yellow]}: {$green}downloaded, {} hash matches{/$}",
+                name, algorithm
+            ),
+            Ok(_) => println!(
+                "  {[bold+yellow]}: {$bold+intense+red}downloaded, but UNVERIFIED{/$} (no hash was published, one was calculated for local integrity checks)",
+                name
+            ),
+            Err(err @ RinthaError::BadFileHash { .. }) => println!(
+                "  {[bold+yellow]}: {$bold+red}{} The file was discarded.{/$}",
+                name, err
+            ),
+            Err(err) => println!("  {[bold+yellow]}: {$bold+red}{}{/$}", name, err),
+        }
+
+        if result.is_ok() {
+            self.succeeded += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+
+    /// Prints the summary and returns how many downloads failed.
+    pub(crate) fn finish(&self) -> usize {
+        println!(
+            "{$bold}Downloads:{/$} {[bold+green]} succeeded, {[bold+red]} failed",
+            self.succeeded, self.failed
+        );
+        self.failed
+    }
+}
+
+/// Shows `plan`, asks whether to go on if it installs more than one mod or replaces one (unless
+/// `yes` is set) and installs it into the current profile.
+pub(crate) fn install_plan(
+    program_config: &mut FullConfig,
+    plan: Vec<InstallStep>,
+    yes: bool,
+) -> Result<(), RinthaError> {
+    println!("{$bold}Install plan:{/$}");
+    for step in &plan {
+        match (&step.required_by, &step.replaces) {
+            (_, Some(old)) => println!(
+                "  {[bold+yellow]} {[magenta]} (replacing {[magenta]})",
+                step.cmod.title, step.version.version_number, old.installed_version_number
+            ),
+            (None, None) => println!(
+                "  {[bold+yellow]} {[magenta]}",
+                step.cmod.title, step.version.version_number
+            ),
+            (Some(parent), None) => println!(
+                "  {[bold+yellow]} {[magenta]} (required by {[blue]})",
+                step.cmod.title, step.version.version_number, parent
+            ),
+        }
+    }
+    println!();
+
+    let replacing = plan.iter().any(|step| step.replaces.is_some());
+    if !yes && (plan.len() > 1 || replacing) && !confirm("Install all of these mods?", true)? {
+        println!("{$bold}Nothing was installed.{/$}");
+        return Ok(());
+    }
+
+    // everything gets downloaded (or taken from the cache) and verified before anything is
+    // put in place
+    let current_prof = program_config.current_profile.clone();
+    let mut report = DownloadReport::default();
+    let result = install::install(program_config, plan, &mut |event| match event {
+        Event::Downloading { count } => {
+            println!("{$bold}Downloading {} mod(s)...{/$}", count)
+        }
+        Event::Downloaded { name, result } => report.add(name, result),
+        Event::Committing => {
+            report.finish();
+            println!(
+                "{$bold}Saving to the profile directory ({[yellow]}), the minecraft mods directory and the manifest...{/$}",
+                current_prof
+            );
+        }
+        _ => {}
+    });
+
+    if let Err(err @ RinthaError::DownloadsFailed(_)) = result {
+        report.finish();
+        println!("{$bold+red}Not all mods could be downloaded, cancelling transaction...{/$}");
+        return Err(err);
+    }
+    output::records(&result?);
+
+    println!("{$bold+green}Successfully installed the mod!{/$}");
+    Ok(())
+}
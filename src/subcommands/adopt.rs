@@ -0,0 +1,67 @@
+use super::confirm;
+use crate::output;
+use bunt::println;
+use rintha::common::{FullConfig, RinthaError};
+use rintha::ops::{adopt, Event};
+
+pub fn adopt(program_config: &mut FullConfig, yes: bool) -> Result<(), RinthaError> {
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    println!("{$bold}Profile:{/$} {[bold+yellow]}", profile.name);
+
+    let found = adopt::scan(program_config, &mut |event| {
+        if let Event::Identifying { name } = event {
+            println!("{$bold}Looking up {[yellow]}...{/$}", name);
+        }
+    })?;
+
+    if !found.untracked {
+        println!("{$bold+green}Success:{/$} {$bold}There are no untracked jars in the mods directory!{/$}");
+        return Ok(());
+    }
+
+    for filename in &found.duplicates {
+        println!(
+            "{$bold+intense+red}NOTE{/$}: {$bold}{} is another copy of an installed mod, skipping...{/$}",
+            filename
+        );
+    }
+    println!();
+
+    if !found.adoptable.is_empty() {
+        println!("{$bold}Identified on Modrinth:{/$}");
+        for cmod in &found.adoptable {
+            println!(
+                "  {[yellow]}: {[bold+yellow]} {[magenta]} [{[blue]}]",
+                cmod.current_filename, cmod.title, cmod.installed_version_number, cmod.id.0
+            );
+        }
+    }
+
+    let mut unmanaged: Vec<&String> = found.unidentified.iter().chain(&found.duplicates).collect();
+    unmanaged.sort();
+    if !unmanaged.is_empty() {
+        println!("{$bold+intense+red}NOTE{/$}: {$bold}These jars couldn't be identified and stay unmanaged:{/$}");
+        for filename in unmanaged {
+            println!("  {[yellow]}", filename);
+        }
+    }
+
+    if found.adoptable.is_empty() {
+        return Ok(());
+    }
+
+    if !yes && !confirm("Add the identified mods to this profile?", true)? {
+        println!("{$bold}Nothing was adopted.{/$}");
+        return Ok(());
+    }
+
+    let adopted = adopt::adopt(program_config, found.adoptable)?;
+    output::records(&adopted);
+
+    println!(
+        "{$bold+green}Success:{/$} {$bold}Adopted {} mod(s) into the profile!{/$}",
+        adopted.len()
+    );
+
+    Ok(())
+}
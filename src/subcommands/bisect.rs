@@ -0,0 +1,139 @@
+use crate::output::{self, Record};
+use crate::BisectArg;
+use bunt::println;
+use rintha::common::{Bisect, ConfigMod, FullConfig, ModID, RinthaError};
+use rintha::ops::bisect;
+use serde::Serialize;
+
+pub fn bisect(program_config: &mut FullConfig, op: BisectArg) -> Result<(), RinthaError> {
+    println!(
+        "{$bold}Profile:{/$} {[bold+yellow]}",
+        program_config.current_profile
+    );
+
+    let state = match op {
+        BisectArg::Start => {
+            let state = bisect::start(program_config)?;
+            println!(
+                "{$bold}Bisecting {} enabled mod(s), the game is assumed to be broken with all of them.{/$}",
+                state.suspects.len()
+            );
+            state
+        }
+        BisectArg::Good => bisect::mark(program_config, true)?,
+        BisectArg::Bad => bisect::mark(program_config, false)?,
+        BisectArg::Reset => return bisect_reset(program_config),
+    };
+
+    report(program_config, &state);
+    Ok(())
+}
+
+/// Where the bisect stands, `culprit` is only set once a single suspect is left.
+#[derive(Serialize)]
+struct BisectRecord<'a> {
+    steps: usize,
+    done: bool,
+    culprit: Option<&'a str>,
+    suspects: Vec<&'a str>,
+    enabled: Vec<&'a str>,
+}
+
+impl<'a> BisectRecord<'a> {
+    fn new(state: &'a Bisect) -> Self {
+        let done = state.is_done();
+        BisectRecord {
+            steps: state.steps,
+            done,
+            culprit: match state.suspects.as_slice() {
+                [culprit] if done => Some(culprit.0.as_str()),
+                _ => None,
+            },
+            suspects: state.suspects.iter().map(|id| id.0.as_str()).collect(),
+            enabled: state.enabled.iter().map(|id| id.0.as_str()).collect(),
+        }
+    }
+}
+
+impl Record for BisectRecord<'_> {
+    const COLUMNS: &'static [&'static str] = &["steps", "done", "culprit", "suspects", "enabled"];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.steps.to_string(),
+            self.done.to_string(),
+            self.culprit.unwrap_or_default().into(),
+            self.suspects.join(","),
+            self.enabled.join(","),
+        ]
+    }
+}
+
+fn report(program_config: &FullConfig, state: &Bisect) {
+    output::record(&BisectRecord::new(state));
+
+    let mods: &[ConfigMod] = program_config
+        .get_profile(program_config.current_profile.as_str())
+        .ok()
+        .and_then(|profile| profile.mods.as_deref())
+        .unwrap_or_default();
+    // a suspect might have been removed since
+    let title = |id: &ModID| {
+        mods.iter()
+            .find(|cmod| cmod.id.0 == id.0)
+            .map_or(id.0.clone(), |cmod| cmod.title.clone())
+    };
+
+    if !state.is_done() {
+        let testing = state
+            .suspects
+            .iter()
+            .filter(|suspect| state.enabled.iter().any(|id| id.0 == suspect.0))
+            .count();
+        println!(
+            "{$bold}Step {}:{/$} {} suspect(s) left with {} of them enabled, {} mod(s) in total counting what they need.",
+            state.steps + 1,
+            state.suspects.len(),
+            testing,
+            state.enabled.len()
+        );
+        println!(
+            "{$bold}About {} more step(s) to go. Start the game, then run `rintha bisect good` if it works or `rintha bisect bad` if it doesn't.{/$}",
+            state.suspects.len().ilog2()
+        );
+        return;
+    }
+
+    match state.suspects.as_slice() {
+        [] => println!("{$bold}Every suspect was marked good, no single mod breaks the game.{/$}"),
+        [culprit] => println!(
+            "{$bold+green}Found it:{/$} {[bold+yellow]} [{[blue]}] breaks the game, after {} step(s).",
+            title(culprit),
+            culprit.0,
+            state.steps
+        ),
+        suspects => {
+            println!("{$bold}These mods need each other and can't be split up any further:{/$}");
+            for suspect in suspects {
+                println!(
+                    "  {[bold+yellow]} [{[blue]}]",
+                    title(suspect),
+                    suspect.0
+                );
+            }
+        }
+    }
+    println!("{$bold}Run `rintha bisect reset` to get the mods enabled like they were before.{/$}");
+}
+
+fn bisect_reset(program_config: &mut FullConfig) -> Result<(), RinthaError> {
+    let changed = bisect::reset(program_config)?;
+    output::records(&changed);
+
+    println!(
+        "{$bold+green}Success:{/$} {$bold}The bisect is over, {} mod(s) were enabled or disabled again like before it started.{/$}",
+        changed.len()
+    );
+
+    Ok(())
+}
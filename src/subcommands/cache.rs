@@ -0,0 +1,148 @@
+use crate::output::{self, Record};
+use crate::CacheArg;
+use bunt::println;
+use rintha::cache::{self, cache_dir, CacheEntry};
+use rintha::common::{FullConfig, RinthaError};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+pub fn cache(program_config: &FullConfig, op: CacheArg) -> Result<(), RinthaError> {
+    match op {
+        CacheArg::List => cache_list(program_config)?,
+        CacheArg::Prune => cache_prune(program_config)?,
+        CacheArg::Clear => cache_clear()?,
+    }
+
+    Ok(())
+}
+
+/// A jar in the cache and the mods using it.
+#[derive(Serialize)]
+struct CacheRecord<'a> {
+    sha1: &'a str,
+    size: u64,
+    path: String,
+    used_by: Vec<CacheUser<'a>>,
+}
+
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct CacheUser<'a> {
+    profile: &'a str,
+    title: &'a str,
+}
+
+impl<'a> CacheRecord<'a> {
+    fn new(entry: &'a CacheEntry, used_by: Vec<CacheUser<'a>>) -> Self {
+        CacheRecord {
+            sha1: entry.sha1.as_str(),
+            size: entry.size,
+            path: entry.path.display().to_string(),
+            used_by,
+        }
+    }
+}
+
+impl Record for CacheRecord<'_> {
+    const COLUMNS: &'static [&'static str] = &["sha1", "size", "path", "used_by"];
+
+    fn fields(&self) -> Vec<String> {
+        let used_by: Vec<String> = self
+            .used_by
+            .iter()
+            .map(|user| format!("{}/{}", user.profile, user.title))
+            .collect();
+
+        vec![
+            self.sha1.into(),
+            self.size.to_string(),
+            self.path.clone(),
+            used_by.join(","),
+        ]
+    }
+}
+
+/// Sums up the size of `entries` in MiB.
+fn total_size(entries: &[CacheEntry]) -> f64 {
+    entries.iter().map(|entry| entry.size).sum::<u64>() as f64 / (1024.0 * 1024.0)
+}
+
+fn cache_list(program_config: &FullConfig) -> Result<(), RinthaError> {
+    // sha1 -> mods using it
+    let mut users: HashMap<&str, Vec<CacheUser>> = HashMap::new();
+    for (name, prof) in &program_config.profiles {
+        for cmod in prof.mods.iter().flatten() {
+            users
+                .entry(cmod.sha1.as_str())
+                .or_default()
+                .push(CacheUser {
+                    profile: name.as_str(),
+                    title: cmod.title.as_str(),
+                });
+        }
+    }
+
+    let entries = cache::entries()?;
+    for users in users.values_mut() {
+        users.sort();
+    }
+    let records: Vec<CacheRecord> = entries
+        .iter()
+        .map(|entry| CacheRecord::new(entry, users.remove(entry.sha1.as_str()).unwrap_or_default()))
+        .collect();
+    output::records(&records);
+
+    let dir = cache_dir()?;
+    println!("{$bold}Cache:{/$} {[bold+yellow]}", dir.display());
+
+    for record in &records {
+        println!(
+            "{[bold+blue]} ({[green]} KiB)",
+            record.sha1,
+            record.size / 1024
+        );
+        if record.used_by.is_empty() {
+            println!("  {$italic+red}not used by any profile{/$}");
+        }
+        for user in &record.used_by {
+            println!("  {[bold+yellow]} in {[magenta]}", user.title, user.profile);
+        }
+    }
+
+    println!(
+        "{$bold}{[blue]} jar(s), {[blue]:.2} MiB in total{/$}",
+        entries.len(),
+        total_size(&entries)
+    );
+
+    Ok(())
+}
+
+fn cache_prune(program_config: &FullConfig) -> Result<(), RinthaError> {
+    let keep: HashSet<&str> = program_config
+        .profiles
+        .values()
+        .flat_map(|prof| prof.mods.iter().flatten())
+        .map(|cmod| cmod.sha1.as_str())
+        .collect();
+
+    let removed = cache::prune(&keep)?;
+    let records: Vec<CacheRecord> = removed
+        .iter()
+        .map(|entry| CacheRecord::new(entry, vec![]))
+        .collect();
+    output::records(&records);
+    println!(
+        "{$bold+green}Success:{/$} {$bold}Deleted {} unused jar(s), freeing {:.2} MiB!{/$}",
+        removed.len(),
+        total_size(&removed)
+    );
+
+    Ok(())
+}
+
+fn cache_clear() -> Result<(), RinthaError> {
+    cache::clear()?;
+    println!("{$bold+green}Success:{/$} {$bold}Cleared the cache!{/$}");
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use crate::output;
+use bunt::println;
+use rintha::common::{ConfigMod, FullConfig, RinthaError};
+use rintha::ops::{select, toggle};
+
+/// Puts the mods matching `targets` back into the mods directory, along with the disabled
+/// mods they need.
+pub fn enable(program_config: &mut FullConfig, targets: Vec<String>) -> Result<(), RinthaError> {
+    let ids = selected_ids(program_config, &targets)?;
+    let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+
+    let enabled = toggle::enable(program_config, &ids)?;
+    output::records(&enabled);
+    report(&enabled, "enabled");
+
+    Ok(())
+}
+
+/// Takes the mods matching `targets` out of the mods directory, they stay in the profile so
+/// `rintha enable` brings back the exact same version.
+pub fn disable(
+    program_config: &mut FullConfig,
+    targets: Vec<String>,
+    force: bool,
+) -> Result<(), RinthaError> {
+    let ids = selected_ids(program_config, &targets)?;
+    let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+
+    let disabled = toggle::disable(program_config, &ids, force)?;
+    output::records(&disabled);
+    report(&disabled, "disabled");
+
+    Ok(())
+}
+
+/// The IDs of the mods of the current profile matching `targets`, after printing which
+/// profile that is.
+fn selected_ids(
+    program_config: &FullConfig,
+    targets: &[String],
+) -> Result<Vec<String>, RinthaError> {
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    println!("{$bold}Profile:{/$} {[bold+yellow]}", profile.name);
+
+    let mods: &[ConfigMod] = match profile.mods.as_deref() {
+        Some(mods) if !mods.is_empty() => mods,
+        _ => return Err(RinthaError::NoModsInstalled),
+    };
+    let targets: Vec<&str> = targets.iter().map(String::as_str).collect();
+    select(mods, &targets)
+}
+
+fn report(changed: &[ConfigMod], state: &str) {
+    if changed.is_empty() {
+        println!(
+            "{$bold+green}Success:{/$} {$bold}Every mod was {} already!{/$}",
+            state
+        );
+        return;
+    }
+
+    for cmod in changed {
+        println!(
+            "  {[bold+yellow]} {[magenta]} [{[blue]}]",
+            cmod.title, cmod.installed_version_number, cmod.id.0
+        );
+    }
+    println!(
+        "{$bold+green}Success:{/$} {$bold}{} mod(s) {}, the mods directory was updated!{/$}",
+        changed.len(),
+        state
+    );
+}
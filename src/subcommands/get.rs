@@ -1,72 +1,142 @@
-use crate::common::{
-    mod_dir, shallow_search, version_list, ConfigMod, FullConfig, ModID, ModReleaseType,
-    RinthaError, VersionID,
+use super::{ensure_interactive, install_plan, read_choice};
+use crate::output;
+use crate::GetArgs;
+use bunt::{print, println};
+use rintha::common::{
+    shallow_search, supports, version_info, version_list, FullConfig, ModID, Profile, RinthaError,
+    ShallowSearchResult, Version,
 };
-use bunt::{eprintln, print, println};
-use downloader::{Download, Downloader};
-use sha1::Digest;
-use std::{fs, io::Write, mem};
-
-pub fn get(
-    program_config: &mut FullConfig,
-    query: String,
-    limit: Option<usize>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    /* config mod variables to commit if tx successful */
-    let c_id: ModID;
-    let c_title: String;
-    let c_author_username: String;
-    let c_small_description: String;
-    let c_latest_mc_ver: String;
-    let c_license: String;
-    let c_sha1: String;
-    /* -------------Version related data-------------- */
-    let c_installed_version_id: VersionID;
-    let c_installed_version_number: String;
-    let c_installed_version_type: ModReleaseType;
-    let c_supported_game_versions: Vec<String>;
-    /* -----------VersionFile related data------------ */
-    let c_current_filename: String;
-    /* ----------------------------------------------- */
-
-    let results = shallow_search(query, limit)?;
+use rintha::ops::install;
+use std::mem;
+
+pub fn get(program_config: &mut FullConfig, args: GetArgs) -> Result<(), RinthaError> {
+    let current_prof = program_config.current_profile.clone();
+    let profile = program_config.get_profile(current_prof.as_str())?;
+
+    let (info, final_choice) = if args.exact || args.version.is_some() {
+        select_exact(&args, profile)?
+    } else {
+        select_searched(&args, profile)?
+    };
+    // a dependency that's asked for at the version it's at only has to be marked as such
+    let same_version = profile
+        .mods
+        .iter()
+        .flatten()
+        .any(|cmod| cmod.id.0 == info.id.0 && cmod.installed_version_id.0 == final_choice.id.0);
+    if same_version {
+        let cmod = install::promote(program_config, info.id.0.as_str())?;
+        output::record(&cmod);
+        println!(
+            "{$bold+green}Success:{/$} {$bold}{[yellow]} was installed as a dependency, it now counts as installed explicitly.{/$}",
+            cmod.title
+        );
+        return Ok(());
+    }
+
+    println!("{$bold}Resolving dependencies...{/$}");
+    let plan = install::plan(
+        program_config,
+        current_prof.as_str(),
+        &info,
+        &final_choice,
+        args.override_pin,
+        args.allow_incompatible,
+    )?;
+
+    install_plan(program_config, plan, args.yes)
+}
+
+/// Lets the user pick a mod out of the search results for the query, then one of its versions.
+fn select_searched(
+    args: &GetArgs,
+    profile: &Profile,
+) -> Result<(ShallowSearchResult, Version), RinthaError> {
+    let results = shallow_search(args.query.clone(), args.limit)?;
     for (n, i) in results.iter().enumerate() {
         println!(
             "{$bold+cyan}[{[blue]}]{/$} {[bold+yellow]} (by {[bold+blue]}): {[italic+cyan]}",
             n, i.title, i.author_username, i.small_description
         );
         println!(
-            "Downloaded {[bold+cyan]} times, licensed under \"{[bold+cyan]}\" and the latest supported mc version is {[bold+cyan]}",
-            i.downloads, i.license, i.latest_mc_ver
+            "Downloaded {[bold+cyan]} times, followed by {[bold+cyan]} people, licensed under \"{[bold+cyan]}\" and the latest supported mc version is {[bold+cyan]}",
+            i.downloads, i.follows, i.license, i.latest_mc_ver
         );
         println!();
     }
 
-    // FIXME: get a proper line reader
-    print!("{$bold}Enter your choice: {/$}");
-    std::io::stdout().flush()?;
-    let mut string = String::new();
-    std::io::stdin().read_line(&mut string)?;
-    let choice1: usize = string.trim().parse()?;
-
-    if choice1 >= results.len() {
-        eprintln!("{$bold}Choice was over the limit, exiting...{/$}");
-        std::process::exit(-1);
-    }
+    let choice1 = read_choice(results.len())?;
 
     println!(
         "{$bold}Getting info for \"{[yellow]}\"...{/$}\n",
         results[choice1].title
     );
 
-    c_id = results[choice1].id.clone();
-    c_title = results[choice1].title.clone();
-    c_author_username = results[choice1].author_username.clone();
-    c_small_description = results[choice1].small_description.clone();
-    c_latest_mc_ver = results[choice1].latest_mc_ver.clone();
-    c_license = results[choice1].license.clone();
-
     let versions = version_list(results[choice1].id.clone())?;
+    let ver = choose_version(args, profile, versions)?;
+
+    Ok((results.into_iter().nth(choice1).unwrap(), ver)) // checked against the length above
+}
+
+/// Looks the query up as a slug or mod ID, no searching involved.
+fn select_exact(
+    args: &GetArgs,
+    profile: &Profile,
+) -> Result<(ShallowSearchResult, Version), RinthaError> {
+    println!(
+        "{$bold}Getting info for \"{[yellow]}\"...{/$}\n",
+        args.query
+    );
+
+    let versions = version_list(ModID(args.query.clone()))?;
+    let ver = choose_version(args, profile, versions)?;
+    let info = version_info(&ver)?;
+
+    Ok((info, ver))
+}
+
+/// Resolves `--version` if it was given, otherwise lists the versions matching the
+/// `--game-version` and `--loader` filters and asks which one to install. Versions that don't
+/// fit the profile are marked and can only be picked with `--allow-incompatible`.
+fn choose_version(
+    args: &GetArgs,
+    profile: &Profile,
+    versions: Vec<Version>,
+) -> Result<Version, RinthaError> {
+    let game_version = args.game_version.as_deref();
+    let loader = args.loader.as_deref();
+    let compatible = |ver: &Version| args.allow_incompatible || profile.supports(ver);
+
+    let versions: Vec<Version> = versions
+        .into_iter()
+        .filter(|ver| supports(ver, game_version, loader))
+        .collect();
+
+    if let Some(spec) = &args.version {
+        let found = if spec == "latest" {
+            versions
+                .into_iter()
+                .filter(|ver| compatible(ver))
+                .max_by(|a, b| a.date_published.cmp(&b.date_published))
+        } else {
+            versions
+                .into_iter()
+                .find(|ver| ver.id.0 == *spec || ver.version_number == *spec)
+        };
+
+        return match found {
+            Some(ver) if compatible(&ver) => Ok(ver),
+            Some(_) => Err(RinthaError::IncompatibleVersion),
+            None => Err(RinthaError::VersionNotFound(spec.clone())),
+        };
+    }
+
+    if versions.is_empty() {
+        return Err(RinthaError::VersionNotFound("the given filters".into()));
+    }
+
+    // the pagination below needs someone pressing enter
+    ensure_interactive()?;
     if versions.len() >= 10 {
         println!("{$bold}10 or more results were returned, press enter after each 5 versions are shown to continue...{/$}");
     }
@@ -77,6 +147,9 @@ pub fn get(
             "{$bold+cyan}[{[blue]}]{/$} {[bold+yellow]} ({[italic+bold+magenta]}) ({[bold+green] :?})",
             i, ver.name, ver.version_number, ver.version_type,
         );
+        if !profile.supports(ver) {
+            println!("{$bold+red}INCOMPATIBLE with this profile{/$}");
+        }
         print!("{$bold}Supported loader(s): {/$}");
         for loader in &ver.loaders {
             print!("{[green]} ", loader);
@@ -99,140 +172,11 @@ pub fn get(
     }
     mem::drop(tmp);
 
-    // FIXME: get a proper line reader
-    print!("{$bold}Enter your choice: {/$}");
-    std::io::stdout().flush()?;
-    let mut string = String::new();
-    std::io::stdin().read_line(&mut string)?;
-    let choice2: usize = string.trim().parse()?;
-
-    if choice2 >= versions.len() {
-        eprintln!("{$bold}Choice was over the limit, exiting...{/$}");
-        std::process::exit(-1);
-    }
-
-    c_installed_version_id = versions[choice2].id.clone();
-    c_installed_version_number = versions[choice2].version_number.clone();
-    c_installed_version_type = versions[choice2].version_type;
-    c_supported_game_versions = versions[choice2].game_versions.clone();
-
-    let final_choice = versions[choice2].clone();
-
-    println!("Downloading {[bold+yellow]}...", final_choice.name);
-    let mut downloader = Downloader::builder()
-        .download_folder(std::path::Path::new("."))
-        .parallel_requests(1)
-        .build()?;
-
-    let modfile = Download::new(final_choice.files[0].url.as_str()).file_name(
-        std::path::Path::new(final_choice.files[0].filename.clone().as_str()),
-    );
-
-    let result = downloader.download(&[modfile])?;
-    for r in result {
-        match r {
-            Err(e) => println!("{$bold}Error occured!{/$} {[bold+red]}", e.to_string()),
-            Ok(s) => println!("{$bold}Success:{/$} {[bold+green]}", &s),
-        };
-    }
-
-    if let Some(hash) = final_choice.files[0].hashes.get("sha1") {
-        let modfile = std::fs::read(final_choice.files[0].filename.clone())?;
-        let sha1_hash = format!("{:x}", sha1::Sha1::digest(&modfile));
-        c_sha1 = sha1_hash.clone();
-        mem::drop(modfile);
-
-        if *hash == sha1_hash {
-            println!("{$bold}Verification:{/$} {$bold+green}Checked sha1 hash of downloaded mod, it matches!{/$}");
-        } else {
-            println!("{$bold}Verification:{/$} {$bold+red}Checked sha1 hash of downloaded mod, it doesn't match! cancelling transaction...{/$}");
-            std::fs::remove_file(final_choice.files[0].filename.clone())?;
-            return Err(Box::new(RinthaError::BadFileHash));
-        }
-    } else {
-        println!("{$bold+intense+red}NOTE{/$}: {$bold}No \"sha1\" hash exists for this mod, this mod is UNVERIFIED but a sha1 hash will be calculated for local integrity checks...{/$}");
-
-        let modfile = std::fs::read(final_choice.files[0].filename.clone())?;
-        let sha1_hash = format!("{:x}", sha1::Sha1::digest(&modfile));
-        c_sha1 = sha1_hash;
-    }
-
-    c_current_filename = final_choice.files[0].filename.clone();
-
-    let mod_manifestation = ConfigMod {
-        id: c_id,
-        title: c_title,
-        author_username: c_author_username,
-        small_description: c_small_description,
-        latest_mc_ver: c_latest_mc_ver,
-        license: c_license,
-        sha1: c_sha1,
-        installed_version_id: c_installed_version_id,
-        installed_version_number: c_installed_version_number,
-        installed_version_type: c_installed_version_type,
-        supported_game_versions: c_supported_game_versions,
-        current_filename: c_current_filename,
-    };
-
-    // FIXME: make this respect custom paths set in config
-    let mod_directory = match mod_dir() {
-        Ok(dir) => dir,
-        Err(err) => match err {
-            RinthaError::UnsupportedPlatform => {
-                println!("{$bold+red}Unsupported platform, no mod directory found...{/$}");
-                std::process::exit(-1);
-            }
-            _ => unreachable!(), // mod_dir only returns unsupported platform on failure.
-        },
-    };
-
-    {
-        // yes i know this is stupid and it does a lot of allocation
-        // but i don't know any other way to do it, please help (FIXME)
-        let current_prof = program_config.current_profile.as_str();
-        let mut edited_prof = program_config.profiles[current_prof].clone();
-
-        match edited_prof.add_mod(mod_manifestation) {
-            Ok(_) => (),
-            Err(err) => {
-                match err {
-                    RinthaError::ModAlreadyInstalled => {
-                        println!("{$bold+intense+red}Another version of this mod is already installed!{/$}");
-                        println!("{$bold}Please check `rintha list`.{/$}");
-                        std::process::exit(-1);
-                    }
-                    _ => unreachable!(), // add_mod only returns ModAlreadyInstalled on error
-                }
-            }
-        };
+    let choice2 = read_choice(versions.len())?;
 
-        program_config
-            .profiles
-            .insert(current_prof.into(), edited_prof);
+    if !compatible(&versions[choice2]) {
+        return Err(RinthaError::IncompatibleVersion);
     }
 
-    println!("{$bold}Finalization...{/$}");
-    let filename = final_choice.files[0].filename.as_str();
-    fs::create_dir_all(program_config.get_current_prof_path()?)?;
-
-    println!(
-        "{$bold}Saving to profile directory... ({[yellow]}){/$}",
-        program_config.current_profile.as_str()
-    );
-    fs::copy(
-        filename,
-        program_config
-            .get_current_prof_path()
-            .unwrap()
-            .join(filename),
-    )?;
-
-    println!("{$bold}Saving to the minecraft mods directory...{/$}");
-    fs::copy(filename, mod_directory.as_path().join(filename))?;
-
-    println!("{$bold}Cleaning up...{/$}");
-    fs::remove_file(filename)?;
-
-    println!("{$bold+green}Successfully installed the mod!{/$}");
-    Ok(())
+    Ok(versions.into_iter().nth(choice2).unwrap()) // checked against the length above
 }
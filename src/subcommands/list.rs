@@ -1,22 +1,29 @@
-use crate::common::FullConfig;
-use bunt::{eprintln, print, println};
+use crate::output;
+use bunt::{print, println};
+use rintha::common::{FullConfig, RinthaError};
+
+pub fn list(program_config: &FullConfig, full_list: bool) -> Result<(), RinthaError> {
+    // machine readable output always has every field, and no mods is just an empty list there
+    if !output::is_human() {
+        let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+        output::records(profile.mods.as_deref().unwrap_or_default());
+        return Ok(());
+    }
 
-pub fn list(program_config: &mut FullConfig, full_list: bool) {
     if full_list {
-        list_full(program_config);
+        list_full(program_config)
     } else {
-        list_normal(program_config);
+        list_normal(program_config)
     }
 }
 
-fn list_normal(fc: &FullConfig) {
+fn list_normal(fc: &FullConfig) -> Result<(), RinthaError> {
     let profname = fc.current_profile.as_str();
     let profile = &fc.profiles[profname];
 
     println!("{$bold}Profile:{/$} {[bold+yellow]}", profile.name);
     if profile.mods.is_none() {
-        eprintln!("{$bold+red}Error:{/$} {$bold}No mods have been installed yet!{/$}");
-        std::process::exit(-1);
+        return Err(RinthaError::NoModsInstalled);
     }
 
     for i in profile.mods.as_ref().unwrap() {
@@ -25,22 +32,32 @@ fn list_normal(fc: &FullConfig) {
             "{$bold+cyan}Mod ID: [{[blue]}]{/$} {[bold+yellow]} (by {[bold+blue]}) ({[bold+intense+green]:?}): {[italic+cyan]}",
             i.id.0, i.title, i.author_username, i.installed_version_type, i.small_description
         );
+        if !i.explicit {
+            println!("{$italic}Installed as a dependency{/$}");
+        }
+        if i.pinned {
+            println!("{$italic}Pinned, updates skip it{/$}");
+        }
+        if i.disabled {
+            println!("{$italic+red}Disabled, not in the mods directory{/$}");
+        }
         println!(
             "Version ID: {[bold+cyan]}, licensed under \"{[bold+cyan]}\" and the latest supported mc version is {[bold+cyan]}",
             i.installed_version_id.0, i.license, i.latest_mc_ver
         );
         println!();
     }
+
+    Ok(())
 }
 
-fn list_full(fc: &FullConfig) {
+fn list_full(fc: &FullConfig) -> Result<(), RinthaError> {
     let profname = fc.current_profile.as_str();
     let profile = &fc.profiles[profname];
 
     println!("{$bold}Profile:{/$} {[bold+yellow]}", profile.name);
     if profile.mods.is_none() {
-        eprintln!("{$bold+red}Error:{/$} {$bold}No mods have been installed yet!{/$}");
-        std::process::exit(-1);
+        return Err(RinthaError::NoModsInstalled);
     }
 
     for i in profile.mods.as_ref().unwrap() {
@@ -60,13 +77,39 @@ fn list_full(fc: &FullConfig) {
             i.installed_version_number
         );
         println!("{$bold+cyan}SHA-1: [{[green]}]{/$}", i.sha1);
+        if !i.sha512.is_empty() {
+            println!("{$bold+cyan}SHA-512: [{[green]}]{/$}", i.sha512);
+        }
         println!("{$bold+cyan}Filename: [{[green]}]{/$}", i.current_filename);
+        match (&i.explicit, &i.installed_for) {
+            (false, Some(parent)) => println!(
+                "{$bold+cyan}Installed as a dependency of: [{[green]}]{/$}",
+                parent.0
+            ),
+            (false, None) => println!("{$bold+cyan}Installed as a dependency{/$}"),
+            (true, _) => println!("{$bold+cyan}Installed explicitly{/$}"),
+        }
+        if i.pinned {
+            println!("{$bold+cyan}Pinned: [{[green]}]{/$}", "yes");
+        }
+        if i.disabled {
+            println!("{$bold+cyan}Disabled: [{[red]}]{/$}", "yes");
+        }
 
         print!("{$bold+cyan}Supported minecraft versions: {/$}");
         for j in &i.supported_game_versions {
             print!("{[intense+green]} ", j);
         }
 
+        if !i.dependencies.is_empty() {
+            print!("\n{$bold+cyan}Requires: {/$}");
+            for j in &i.dependencies {
+                print!("{[intense+green]} ", j.0);
+            }
+        }
+
         println!("\n");
     }
+
+    Ok(())
 }
@@ -0,0 +1,96 @@
+use super::install_plan;
+use crate::output;
+use bunt::println;
+use rintha::common::{version_info, version_list, ConfigMod, FullConfig, RinthaError};
+use rintha::ops::{install, pin, select};
+
+/// Holds the mod matching `target` at its installed version, or at `version` after installing
+/// that first (asking before doing so unless `yes` is set), so updates and `get` leave it
+/// alone.
+pub fn pin(
+    program_config: &mut FullConfig,
+    target: &str,
+    version: Option<String>,
+    yes: bool,
+) -> Result<(), RinthaError> {
+    let cmod = find_mod(program_config, target)?;
+
+    if let Some(spec) = version.filter(|spec| {
+        *spec != cmod.installed_version_id.0 && *spec != cmod.installed_version_number
+    }) {
+        println!(
+            "{$bold}Getting the versions of {[yellow]}...{/$}",
+            cmod.title
+        );
+        let ver = version_list(cmod.id.clone())?
+            .into_iter()
+            .find(|ver| ver.id.0 == spec || ver.version_number == spec)
+            .ok_or(RinthaError::VersionNotFound(spec))?;
+        let info = version_info(&ver)?;
+
+        println!("{$bold}Resolving dependencies...{/$}");
+        let current_prof = program_config.current_profile.clone();
+        let mut plan = install::plan(
+            program_config,
+            current_prof.as_str(),
+            &info,
+            &ver,
+            true,
+            false,
+        )?;
+        // pinning a dependency doesn't make it asked for
+        plan[0].cmod.pinned = true;
+        plan[0].cmod.explicit = cmod.explicit;
+        plan[0].cmod.installed_for = cmod.installed_for.clone();
+
+        return install_plan(program_config, plan, yes);
+    }
+
+    let cmod = pin::set_pinned(program_config, cmod.id.0.as_str(), true)?;
+    output::record(&cmod);
+    println!(
+        "{$bold+green}Success:{/$} {$bold}Pinned {[yellow]} at {[magenta]}, updates will skip it.{/$}",
+        cmod.title, cmod.installed_version_number
+    );
+
+    Ok(())
+}
+
+/// Lets updates and `get` replace the mod matching `target` again.
+pub fn unpin(program_config: &mut FullConfig, target: &str) -> Result<(), RinthaError> {
+    let cmod = find_mod(program_config, target)?;
+    if !cmod.pinned {
+        output::record(&cmod);
+        println!("{$bold}{[yellow]} isn't pinned.{/$}", cmod.title);
+        return Ok(());
+    }
+
+    let cmod = pin::set_pinned(program_config, cmod.id.0.as_str(), false)?;
+    output::record(&cmod);
+    println!(
+        "{$bold+green}Success:{/$} {$bold}Unpinned {[yellow]}, updates will include it again.{/$}",
+        cmod.title
+    );
+
+    Ok(())
+}
+
+/// The single mod of the current profile matching `target`, after printing which profile
+/// that is.
+fn find_mod(program_config: &FullConfig, target: &str) -> Result<ConfigMod, RinthaError> {
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    println!("{$bold}Profile:{/$} {[bold+yellow]}", profile.name);
+
+    let mods: &[ConfigMod] = profile.mods.as_deref().unwrap_or_default();
+    let ids = select(mods, &[target])?;
+    if ids.len() > 1 {
+        return Err(RinthaError::AmbiguousMod(target.into(), ids.len()));
+    }
+
+    // safe to unwrap, select found it
+    Ok(mods
+        .iter()
+        .find(|cmod| cmod.id.0 == ids[0])
+        .unwrap()
+        .clone())
+}
@@ -0,0 +1,264 @@
+use super::confirm;
+use crate::output::{self, Record};
+use crate::ProfileArg;
+use bunt::println;
+use rintha::common::{unmanaged_dir, FullConfig, Loader, Profile, RinthaError};
+use rintha::ops::profile as ops;
+use serde::Serialize;
+use std::io::IsTerminal;
+
+#[derive(Serialize)]
+struct ProfileRecord<'a> {
+    name: &'a str,
+    current: bool,
+    game_version: Option<&'a str>,
+    loader: Option<&'static str>,
+    directory: String,
+    mod_count: usize,
+}
+
+impl<'a> ProfileRecord<'a> {
+    fn new(program_config: &FullConfig, prof: &'a Profile) -> Result<Self, RinthaError> {
+        Ok(ProfileRecord {
+            name: prof.name.as_str(),
+            current: program_config.current_profile == prof.name,
+            game_version: prof.game_version.as_deref(),
+            loader: prof.loader.map(Loader::as_str),
+            directory: program_config
+                .get_prof_path(prof.name.as_str())?
+                .display()
+                .to_string(),
+            mod_count: prof.mods.as_ref().map_or(0, |mods| mods.len()),
+        })
+    }
+}
+
+impl Record for ProfileRecord<'_> {
+    const COLUMNS: &'static [&'static str] = &[
+        "name",
+        "current",
+        "game_version",
+        "loader",
+        "directory",
+        "mod_count",
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.name.into(),
+            self.current.to_string(),
+            self.game_version.unwrap_or_default().into(),
+            self.loader.unwrap_or_default().into(),
+            self.directory.clone(),
+            self.mod_count.to_string(),
+        ]
+    }
+}
+
+pub fn profile(program_config: &mut FullConfig, op: ProfileArg) -> Result<(), RinthaError> {
+    match op {
+        ProfileArg::New {
+            name,
+            game_version,
+            loader,
+        } => prof_new(program_config, name, game_version, loader)?,
+        ProfileArg::Use(name) => prof_use(program_config, name)?,
+        ProfileArg::Rename { from, to } => prof_rename(program_config, from, to)?,
+        ProfileArg::Clone { from, to } => prof_clone(program_config, from, to)?,
+        ProfileArg::Delete(name) => prof_delete(program_config, name)?,
+        ProfileArg::Show(name) => prof_show(program_config, name)?,
+        ProfileArg::Sync => prof_sync(program_config)?,
+    }
+
+    Ok(())
+}
+
+fn prof_new(
+    program_config: &mut FullConfig,
+    name: String,
+    game_version: Option<String>,
+    loader: Option<Loader>,
+) -> Result<(), RinthaError> {
+    ops::create(program_config, name.as_str(), game_version, loader)?;
+
+    println!(
+        "{$bold+green}Success:{/$} {$bold}Created profile {[yellow]}!{/$}",
+        name
+    );
+    println!(
+        "{$bold}Use `rintha profile use {}` to switch to it.{/$}",
+        name
+    );
+
+    Ok(())
+}
+
+fn prof_use(program_config: &mut FullConfig, name: String) -> Result<(), RinthaError> {
+    program_config.get_profile(name.as_str())?;
+
+    if program_config.current_profile == name {
+        println!("{$bold}Already using profile {[yellow]}.{/$}", name);
+        return Ok(());
+    }
+
+    println!(
+        "{$bold}Switching from {[yellow]} to {[yellow]}...{/$}",
+        program_config.current_profile, name
+    );
+    sync_to(program_config, name.as_str())
+}
+
+fn prof_sync(program_config: &mut FullConfig) -> Result<(), RinthaError> {
+    let name = program_config.current_profile.clone();
+    println!("{$bold}Profile:{/$} {[bold+yellow]}", name);
+    sync_to(program_config, name.as_str())
+}
+
+/// Makes the mods directory hold exactly the mods of the profile `name` and makes it the
+/// current profile, jars that rintha didn't install are only ever moved aside if the user
+/// agrees to it.
+fn sync_to(program_config: &mut FullConfig, name: &str) -> Result<(), RinthaError> {
+    let plan = program_config.plan_sync(name)?;
+
+    let mut move_unmanaged = false;
+    if !plan.unmanaged.is_empty() {
+        println!("{$bold+intense+red}NOTE{/$}: {$bold}These jars in the mods directory weren't installed by rintha:{/$}");
+        for path in &plan.unmanaged {
+            println!("  {[yellow]}", path.display());
+        }
+
+        // scripts can't answer, and leaving the jars where they are is always safe
+        if std::io::stdin().is_terminal() {
+            move_unmanaged = confirm(
+                "Move them out of the mods directory? (they will be kept in the unmanaged folder)",
+                false,
+            )?;
+        } else {
+            println!("{$bold}stdin isn't a terminal, leaving them where they are.{/$}");
+        }
+        if move_unmanaged {
+            let unmanaged_dir = unmanaged_dir()?;
+            println!(
+                "{$bold}Moving them to {[yellow]}{/$}",
+                unmanaged_dir.display()
+            );
+        }
+    }
+
+    if !plan.is_empty() {
+        println!(
+            "{$bold}Removing {[blue]} and copying {[blue]} jar(s)...{/$}",
+            plan.remove.len(),
+            plan.copy.len()
+        );
+    }
+    ops::sync(program_config, name, &plan, move_unmanaged)?;
+
+    if plan.is_empty() {
+        println!("{$bold+green}Success:{/$} {$bold}The mods directory is already up to date!{/$}");
+    } else {
+        println!("{$bold+green}Success:{/$} {$bold}The mods directory now holds the mods of this profile!{/$}");
+    }
+
+    Ok(())
+}
+
+fn prof_rename(
+    program_config: &mut FullConfig,
+    from: String,
+    to: String,
+) -> Result<(), RinthaError> {
+    ops::rename(program_config, from.as_str(), to.as_str())?;
+
+    println!(
+        "{$bold+green}Success:{/$} {$bold}Renamed {[yellow]} to {[yellow]}!{/$}",
+        from, to
+    );
+
+    Ok(())
+}
+
+fn prof_clone(
+    program_config: &mut FullConfig,
+    from: String,
+    to: String,
+) -> Result<(), RinthaError> {
+    ops::clone(program_config, from.as_str(), to.as_str())?;
+
+    println!(
+        "{$bold+green}Success:{/$} {$bold}Cloned {[yellow]} into {[yellow]}!{/$}",
+        from, to
+    );
+
+    Ok(())
+}
+
+fn prof_delete(program_config: &mut FullConfig, name: String) -> Result<(), RinthaError> {
+    ops::delete(program_config, name.as_str())?;
+
+    println!(
+        "{$bold+green}Success:{/$} {$bold}Deleted profile {[yellow]}!{/$}",
+        name
+    );
+
+    Ok(())
+}
+
+fn prof_show(program_config: &FullConfig, name: Option<String>) -> Result<(), RinthaError> {
+    if let Some(name) = name {
+        let prof = program_config.get_profile(name.as_str())?;
+        output::record(&ProfileRecord::new(program_config, prof)?);
+
+        println!("{$bold}Profile:{/$} {[bold+yellow]}", prof.name);
+        println!(
+            "{$bold+cyan}Current: [{[green]}]{/$}",
+            (program_config.current_profile == name)
+        );
+        println!(
+            "{$bold+cyan}Minecraft version: [{[green]}]{/$}",
+            prof.game_version.as_deref().unwrap_or("any")
+        );
+        println!(
+            "{$bold+cyan}Mod loader: [{[green]}]{/$}",
+            prof.loader.map_or("any", Loader::as_str)
+        );
+        let dir = program_config.get_prof_path(name.as_str())?;
+        println!("{$bold+cyan}Directory: [{[green]}]{/$}", dir.display());
+
+        let mods = prof.mods.as_deref().unwrap_or_default();
+        println!("{$bold+cyan}Mod count: [{[green]}]{/$}", mods.len());
+        for cmod in mods {
+            println!(
+                "  {[bold+yellow]} ({[italic+magenta]}) [{[blue]}]",
+                cmod.title, cmod.installed_version_number, cmod.id.0
+            );
+        }
+    } else {
+        let mut names: Vec<&String> = program_config.profiles.keys().collect();
+        names.sort();
+
+        let records = names
+            .iter()
+            .map(|name| ProfileRecord::new(program_config, &program_config.profiles[*name]))
+            .collect::<Result<Vec<_>, _>>()?;
+        output::records(&records);
+
+        for name in names {
+            let count = program_config.profiles[name]
+                .mods
+                .as_ref()
+                .map_or(0, |mods| mods.len());
+
+            if *name == program_config.current_profile {
+                println!(
+                    "{$bold+green}*{/$} {[bold+yellow]} ({[blue]} mods)",
+                    name, count
+                );
+            } else {
+                println!("  {[bold]} ({[blue]} mods)", name, count);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,92 +1,205 @@
-use crate::{
-    common::{mod_dir, FullConfig},
-    RemoveArg,
-};
-use bunt::{eprintln, print, println};
-use femtorinth::data_structures::ModID;
-use std::fs;
+use super::{confirm, read_line};
+use crate::{output, RemoveArg};
+use bunt::println;
+use rintha::common::{ConfigMod, FullConfig, RinthaError};
+use rintha::ops::{deps, remove as ops, select};
+use std::collections::BTreeSet;
 
 // rem? rem??? REM????
 // rem is literally best girl
 
-pub fn remove(
-    program_config: &mut FullConfig,
-    op: RemoveArg,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn remove(program_config: &mut FullConfig, op: RemoveArg) -> Result<(), RinthaError> {
     match op {
-        RemoveArg::Guided => rem_guided(program_config)?,
-        RemoveArg::ModID(mod_id) => rem_mod_id(program_config, mod_id)?,
-        RemoveArg::Unknown => unreachable!(),
+        RemoveArg::Guided { force } => rem_guided(program_config, force)?,
+        RemoveArg::Mods {
+            targets,
+            force,
+            yes,
+        } => rem_mods(program_config, targets, force, yes)?,
+        RemoveArg::All { yes } => rem_all(program_config, yes)?,
     }
 
     Ok(())
 }
 
-fn rem_mod_id(
-    program_config: &mut FullConfig,
-    mod_id: ModID,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let profdir = program_config.get_current_prof_path()?;
+/// Removes the mods that were installed as dependencies and aren't needed anymore.
+pub fn autoremove(program_config: &mut FullConfig, yes: bool) -> Result<(), RinthaError> {
+    let ids = deps::orphans(installed_mods(program_config)?);
+    if ids.is_empty() {
+        output::records::<ConfigMod>(&[]);
+        println!("{$bold+green}Success:{/$} {$bold}Every installed mod is still needed!{/$}");
+        return Ok(());
+    }
+
+    remove_selected(program_config, ids, false, !yes)
+}
+
+/// The mods of the current profile, after printing which profile that is.
+fn installed_mods(program_config: &FullConfig) -> Result<&[ConfigMod], RinthaError> {
     let profname = program_config.current_profile.as_str();
-    let profile = &program_config.profiles[profname];
+    let profile = program_config.get_profile(profname)?;
 
     println!("{$bold}Profile:{/$} {[bold+yellow]}", profile.name);
 
-    if program_config.profiles[program_config.current_profile.as_str()]
-        .mods
-        .is_none()
-    {
-        eprintln!("{$bold+red}Error:{/$} {$bold}No mods installed.{/$}");
-        std::process::exit(-1);
+    match profile.mods.as_deref() {
+        Some(mods) if !mods.is_empty() => Ok(mods),
+        _ => Err(RinthaError::NoModsInstalled),
     }
+}
+
+fn rem_mods(
+    program_config: &mut FullConfig,
+    targets: Vec<String>,
+    force: bool,
+    yes: bool,
+) -> Result<(), RinthaError> {
+    let mods = installed_mods(program_config)?;
+    let targets: Vec<&str> = targets.iter().map(String::as_str).collect();
+    let ids = select(mods, &targets)?;
+
+    // a single mod picked by its exact ID needs no confirmation, like it always did
+    let ask = !yes && (ids.len() > 1 || ids[0] != targets[0]);
+    remove_selected(program_config, ids, force, ask)
+}
 
-    // FIXME: probably unneeded allocation, help?
-    let idx = match program_config.profiles[program_config.current_profile.as_str()]
-        .mods
-        .clone()
-        .unwrap()
+fn rem_all(program_config: &mut FullConfig, yes: bool) -> Result<(), RinthaError> {
+    let ids = installed_mods(program_config)?
         .iter()
-        .position(|val| *val.id.0 == mod_id.0)
-    {
-        Some(idx) => idx,
-        None => {
-            eprintln!(
-                "{$bold+red}Error:{/$} {$bold}No mod with ID '{}' found.{/$}",
-                mod_id.0
-            );
-            std::process::exit(-1);
-        }
-    };
+        .map(|cmod| cmod.id.0.clone())
+        .collect();
 
-    let mut newmods = program_config.profiles[program_config.current_profile.as_str()].clone();
-    let rmod = newmods.mods.as_mut().unwrap().remove(idx);
+    // nothing that stays behind could depend on anything
+    remove_selected(program_config, ids, true, !yes)
+}
 
-    program_config
-        .profiles
-        .insert(program_config.current_profile.clone(), newmods);
+fn rem_guided(program_config: &mut FullConfig, force: bool) -> Result<(), RinthaError> {
+    let mods = installed_mods(program_config)?;
 
-    fs::remove_file(profdir.join(rmod.current_filename.as_str()))?;
-    fs::remove_file(mod_dir()?.join(rmod.current_filename.as_str()))?;
+    for (n, cmod) in mods.iter().enumerate() {
+        println!(
+            "{$bold+cyan}[{[blue]}]{/$} {[bold+yellow]} ({[italic+magenta]}) [{[blue]}]",
+            n, cmod.title, cmod.installed_version_number, cmod.id.0
+        );
+    }
+    println!();
+    println!("{$bold}Pick mods by their number, a range like 2-5 or part of their title, separated by commas.{/$}");
 
-    println!("{$bold+green}Success:{/$} {$bold}Removed from current profile, the mods directory and the manifest!{/$}");
+    let selection = read_line("Mods to remove")?;
+    let ids = parse_selection(selection.as_str(), mods)?
+        .into_iter()
+        .map(|idx| mods[idx].id.0.clone())
+        .collect();
 
-    Ok(())
+    remove_selected(program_config, ids, force, true)
 }
 
-fn rem_guided(program_config: &mut FullConfig) -> Result<(), Box<dyn std::error::Error>> {
-    let profdir = program_config.get_current_prof_path()?;
-    let profname = program_config.current_profile.as_str();
-    let profile = &program_config.profiles[profname];
+/// Lists the mods with the IDs in `ids`, asks whether to go on if `ask` is set and removes
+/// them together.
+fn remove_selected(
+    program_config: &mut FullConfig,
+    ids: Vec<String>,
+    force: bool,
+    ask: bool,
+) -> Result<(), RinthaError> {
+    let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    let mods = profile.mods.as_deref().unwrap_or_default();
+
+    // checked up front so nobody confirms a removal that's refused anyway
+    if !force {
+        ops::check_dependents(mods, &ids)?;
+    }
 
-    println!("{$bold}Profile:{/$} {[bold+yellow]}", profile.name);
+    if ask {
+        println!("{$bold}These mods will be removed:{/$}");
+        for cmod in mods {
+            if ids.contains(&cmod.id.0.as_str()) {
+                println!("  {[bold+yellow]} [{[blue]}]", cmod.title, cmod.id.0);
+            }
+        }
 
-    if program_config.profiles[program_config.current_profile.as_str()]
-        .mods
-        .is_none()
-    {
-        eprintln!("{$bold+red}Error:{/$} {$bold}No mods installed.{/$}");
-        std::process::exit(-1);
+        if !confirm("Remove them?", false)? {
+            println!("{$bold}Nothing was removed.{/$}");
+            return Ok(());
+        }
     }
 
+    let removed = ops::remove(program_config, &ids, force)?;
+    output::records(&removed);
+
+    println!(
+        "{$bold+green}Success:{/$} {$bold}Removed {} mod(s) from current profile, the mods directory and the manifest!{/$}",
+        removed.len()
+    );
+
     Ok(())
 }
+
+/// Turns a comma separated list of indices, ranges like `2-5` and parts of titles into the
+/// indices of the picked mods, in order and without duplicates.
+fn parse_selection(selection: &str, mods: &[ConfigMod]) -> Result<BTreeSet<usize>, RinthaError> {
+    let invalid = |part: &str| RinthaError::InvalidChoice(part.into());
+    let mut selected = BTreeSet::new();
+
+    for part in selection
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        if let Ok(idx) = part.parse::<usize>() {
+            if idx >= mods.len() {
+                return Err(invalid(part));
+            }
+            selected.insert(idx);
+            continue;
+        }
+
+        let range = part.split_once('-').and_then(|(from, to)| {
+            Some((
+                from.trim().parse::<usize>().ok()?,
+                to.trim().parse::<usize>().ok()?,
+            ))
+        });
+        if let Some((from, to)) = range {
+            if from > to || to >= mods.len() {
+                return Err(invalid(part));
+            }
+            selected.extend(from..=to);
+            continue;
+        }
+
+        let matches = title_matches(part, mods);
+        if matches.is_empty() {
+            return Err(invalid(part));
+        }
+        selected.extend(matches);
+    }
+
+    if selected.is_empty() {
+        return Err(invalid(selection));
+    }
+    Ok(selected)
+}
+
+/// The mods whose title contains `part`, ignoring case. If there are none, the ones whose
+/// title has the characters of `part` in the same order (so "fapi" finds "Fabric API").
+fn title_matches(part: &str, mods: &[ConfigMod]) -> Vec<usize> {
+    let part = part.to_lowercase();
+    let titles: Vec<String> = mods.iter().map(|cmod| cmod.title.to_lowercase()).collect();
+
+    let contains: Vec<usize> = (0..mods.len())
+        .filter(|idx| titles[*idx].contains(part.as_str()))
+        .collect();
+    if !contains.is_empty() {
+        return contains;
+    }
+
+    (0..mods.len())
+        .filter(|idx| {
+            let mut title = titles[*idx].chars();
+            part.chars()
+                .filter(|c| !c.is_whitespace())
+                .all(|c| title.any(|t| t == c))
+        })
+        .collect()
+}
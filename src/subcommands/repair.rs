@@ -0,0 +1,67 @@
+use super::DownloadReport;
+use bunt::{eprintln, println};
+use rintha::common::{FullConfig, RinthaError};
+use rintha::ops::{repair, Event};
+
+pub fn repair(program_config: &FullConfig) -> Result<(), RinthaError> {
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    println!("{$bold}Profile:{/$} {[bold+yellow]}", profile.name);
+
+    let mut report = None;
+    let outcome = repair::repair(program_config, &mut |event| match event {
+        Event::Repairing { name } => println!("{$bold}Repairing {[yellow]}...{/$}", name),
+        Event::Downloading { count } => {
+            println!("{$bold}Downloading {} mod(s)...{/$}", count);
+            report = Some(DownloadReport::default());
+        }
+        Event::Downloaded { name, result } => {
+            if let Some(report) = report.as_mut() {
+                report.add(name, result);
+            }
+        }
+        Event::Failed {
+            name,
+            error: RinthaError::BadFileHash { .. },
+        } => {
+            println!("{$bold}Verification:{/$} {$bold+red}The file of {[yellow]} doesn't match the sha1 in the manifest! skipping...{/$}", name);
+        }
+        Event::Failed { error, .. } => eprintln!("{$bold+red}Error:{/$} {[bold]}", error),
+        Event::Committing => {
+            if let Some(report) = report.take() {
+                report.finish();
+            }
+        }
+        _ => {}
+    })?;
+
+    if outcome.repaired.is_empty() && outcome.unrepaired() == 0 {
+        println!("{$bold+green}Success:{/$} {$bold}Nothing needs repairing!{/$}");
+    }
+
+    if !outcome.gone.is_empty() {
+        println!("{$bold+intense+red}NOTE{/$}: {$bold}These versions no longer exist on Modrinth and couldn't be repaired:{/$}");
+        for cmod in &outcome.gone {
+            println!(
+                "  {[bold+yellow]} {[magenta]} [{[blue]}]",
+                cmod.title, cmod.installed_version_number, cmod.id.0
+            );
+        }
+    }
+
+    if outcome.extra > 0 {
+        println!("{$bold+intense+red}NOTE{/$}: {$bold}{} extra file(s) were left alone, see `rintha verify` and `rintha profile sync`.{/$}", outcome.extra);
+    }
+
+    match outcome.unrepaired() {
+        0 => {
+            if !outcome.repaired.is_empty() {
+                println!(
+                    "{$bold+green}Success:{/$} {$bold}Repaired {} mod(s)!{/$}",
+                    outcome.repaired.len()
+                );
+            }
+            Ok(())
+        }
+        unrepaired => Err(RinthaError::Drift(unrepaired)),
+    }
+}
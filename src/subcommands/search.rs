@@ -0,0 +1,48 @@
+use crate::output;
+use bunt::println;
+use rintha::common::{search as search_mods, RinthaError, SearchQuery};
+
+pub fn search(query: &SearchQuery) -> Result<(), RinthaError> {
+    let page = search_mods(query)?;
+    output::records(&page.hits);
+
+    if page.hits.is_empty() {
+        println!("{$bold}No mods were found.{/$}");
+        return Ok(());
+    }
+
+    for i in &page.hits {
+        println!(
+            "{$bold+cyan}[{[blue]}]{/$} {[bold+yellow]} (by {[bold+blue]}): {[italic+cyan]}",
+            i.slug, i.title, i.author_username, i.small_description
+        );
+        println!(
+            "Downloaded {[bold+cyan]} times, followed by {[bold+cyan]} people, licensed under \"{[bold+cyan]}\" and the latest supported mc version is {[bold+cyan]}",
+            i.downloads, i.follows, i.license, i.latest_mc_ver
+        );
+        if !i.categories.is_empty() {
+            println!("Categories: {[bold+cyan]}", i.categories.join(", "));
+        }
+        println!();
+    }
+
+    // modrinth can hand back a different limit than the one asked for
+    let per_page = page.limit.max(1);
+    let pages = page.total_hits.div_ceil(per_page);
+    println!(
+        "{$bold}Showing {}-{} of {} result(s), page {} of {}{/$}",
+        page.offset + 1,
+        page.offset + page.hits.len(),
+        page.total_hits,
+        page.offset / per_page + 1,
+        pages
+    );
+    if page.offset + page.hits.len() < page.total_hits {
+        println!(
+            "{$bold}Use `--page {}` to see more, or `rintha get --exact <slug>` to install one.{/$}",
+            page.offset / per_page + 2
+        );
+    }
+
+    Ok(())
+}
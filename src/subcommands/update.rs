@@ -0,0 +1,173 @@
+use super::{confirm, DownloadReport};
+use crate::output::{self, Record};
+use bunt::println;
+use rintha::common::{ConfigMod, FullConfig, ModID, RinthaError, Version};
+use rintha::ops::update::{self, Update};
+use rintha::ops::Event;
+use serde::Serialize;
+
+/// An available update, with the full listing of the version it would update to.
+#[derive(Serialize)]
+struct UpdateRecord<'a> {
+    id: &'a str,
+    title: &'a str,
+    installed_version_id: &'a str,
+    installed_version_number: &'a str,
+    version: &'a Version,
+}
+
+impl<'a> UpdateRecord<'a> {
+    fn new(update: &'a Update) -> Self {
+        let cmod = &update.cmod;
+        UpdateRecord {
+            id: cmod.id.0.as_str(),
+            title: cmod.title.as_str(),
+            installed_version_id: cmod.installed_version_id.0.as_str(),
+            installed_version_number: cmod.installed_version_number.as_str(),
+            version: &update.version,
+        }
+    }
+}
+
+impl Record for UpdateRecord<'_> {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "installed_version_number",
+        "version_number",
+        "version_id",
+        "version_type",
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.id.into(),
+            self.title.into(),
+            self.installed_version_number.into(),
+            self.version.version_number.clone(),
+            self.version.id.0.clone(),
+            format!("{:?}", self.version.version_type).to_lowercase(),
+        ]
+    }
+}
+
+pub fn update(
+    program_config: &mut FullConfig,
+    mod_id: Option<ModID>,
+    check: bool,
+    yes: bool,
+) -> Result<(), RinthaError> {
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    println!("{$bold}Profile:{/$} {[bold+yellow]}", profile.name);
+
+    let found = update::check(
+        program_config,
+        mod_id.as_ref().map(|id| id.0.as_str()),
+        &mut |event| {
+            if let Event::Checking { name } = event {
+                println!("{$bold}Checking {[yellow]}...{/$}", name);
+            }
+        },
+    )?;
+    println!();
+
+    if !found.held.is_empty() {
+        println!("{$bold}Held:{/$}");
+        for cmod in &found.held {
+            println!(
+                "  {[bold+yellow]}: pinned at {[magenta]}",
+                cmod.title, cmod.installed_version_number
+            );
+        }
+        println!();
+    }
+
+    if !found.not_updatable.is_empty() {
+        println!("{$bold+intense+red}NOTE{/$}: {$bold}The mod loaders of these mods weren't recorded and their installed versions are gone from Modrinth, so they can't be updated:{/$}");
+        for cmod in &found.not_updatable {
+            println!(
+                "  {[bold+yellow]} {[magenta]} [{[blue]}]",
+                cmod.title, cmod.installed_version_number, cmod.id.0
+            );
+        }
+        println!();
+    }
+
+    let records: Vec<UpdateRecord> = found.updates.iter().map(UpdateRecord::new).collect();
+    output::records(&records);
+
+    if found.updates.is_empty() {
+        println!("{$bold+green}Everything is up to date!{/$}");
+        return Ok(());
+    }
+
+    println!("{$bold}Available updates:{/$}");
+    for Update {
+        cmod,
+        version,
+        installs,
+        ..
+    } in &found.updates
+    {
+        println!(
+            "  {[bold+yellow]}: {[magenta]} -> {[bold+green]} ({:?})",
+            cmod.title, cmod.installed_version_number, version.version_number, version.version_type
+        );
+        for step in installs {
+            println!(
+                "    {[bold+yellow]} {[magenta]} (new dependency)",
+                step.cmod.title, step.version.version_number
+            );
+        }
+    }
+    println!();
+
+    if check {
+        return Ok(());
+    }
+
+    if !yes && !confirm("Install these updates?", true)? {
+        println!("{$bold}Nothing was updated.{/$}");
+        return Ok(());
+    }
+
+    // failed downloads are skipped, the other mods are still updated
+    let mut report = DownloadReport::default();
+    let outcome = update::update(program_config, found.updates, &mut |event| match event {
+        Event::Downloading { count } => {
+            println!("{$bold}Downloading {} update(s)...{/$}", count)
+        }
+        Event::Downloaded { name, result } => report.add(name, result),
+        Event::Committing => {
+            report.finish();
+        }
+        _ => {}
+    })?;
+    let changed: Vec<ConfigMod> = outcome
+        .updated
+        .iter()
+        .chain(&outcome.installed)
+        .cloned()
+        .collect();
+    output::records(&changed);
+
+    if !outcome.installed.is_empty() {
+        println!("{$bold}Installed new dependencies:{/$}");
+        for cmod in &outcome.installed {
+            println!(
+                "  {[bold+yellow]} {[magenta]}",
+                cmod.title, cmod.installed_version_number
+            );
+        }
+    }
+
+    if outcome.failed > 0 {
+        // the mods that could be downloaded are updated by now, the exit code still has to
+        // tell scripts that not everything went through
+        println!("{$bold}The other mods were updated.{/$}");
+        return Err(RinthaError::DownloadsFailed(outcome.failed));
+    }
+
+    println!("{$bold+green}Success:{/$} {$bold}Updated all mods!{/$}");
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use crate::output::{self, Record};
+use bunt::println;
+use rintha::common::{Drift, DriftKind, FullConfig, RinthaError};
+use rintha::config;
+use serde::Serialize;
+use shellexpand::tilde;
+use std::path::Path;
+
+/// A file that doesn't match the manifest, `kind` is "missing", "modified" or "extra".
+#[derive(Serialize)]
+struct DriftRecord<'a> {
+    kind: &'static str,
+    path: String,
+    mod_id: Option<&'a str>,
+    /// The sha1 the file has now, only for modified files
+    actual_sha1: Option<&'a str>,
+}
+
+impl<'a> DriftRecord<'a> {
+    fn new(problem: &'a Drift) -> Self {
+        let (kind, actual_sha1) = match &problem.kind {
+            DriftKind::Missing => ("missing", None),
+            DriftKind::Modified { actual } => ("modified", Some(actual.as_str())),
+            DriftKind::Extra => ("extra", None),
+        };
+
+        DriftRecord {
+            kind,
+            path: problem.path.display().to_string(),
+            mod_id: problem.mod_id.as_ref().map(|id| id.0.as_str()),
+            actual_sha1,
+        }
+    }
+}
+
+impl Record for DriftRecord<'_> {
+    const COLUMNS: &'static [&'static str] = &["kind", "path", "mod_id", "actual_sha1"];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.kind.into(),
+            self.path.clone(),
+            self.mod_id.unwrap_or_default().into(),
+            self.actual_sha1.unwrap_or_default().into(),
+        ]
+    }
+}
+
+/// Checks the current profile against the manifest, or only the mods directory given with
+/// `--mods-dir` or `--game-dir` against the manifest at `manifest`.
+pub fn verify(program_config: &FullConfig, manifest: Option<String>) -> Result<(), RinthaError> {
+    let loaded: FullConfig;
+    let (fc, only_mod_dir) = match manifest {
+        Some(path) => {
+            // the profile directories here belong to the local config, not to this manifest
+            let mod_dir = program_config
+                .mod_dir_override
+                .clone()
+                .ok_or(RinthaError::ManifestWithoutModDir)?;
+            let mut manifest = config::load_path(Path::new(tilde(path.as_str()).as_ref()))?;
+            manifest.mod_dir_override = Some(mod_dir);
+            loaded = manifest;
+            (&loaded, true)
+        }
+        None => (program_config, false),
+    };
+
+    println!("{$bold}Profile:{/$} {[bold+yellow]}", fc.current_profile);
+
+    let drift = if only_mod_dir {
+        fc.verify_mod_dir(fc.current_profile.as_str())?
+    } else {
+        fc.verify_profile(fc.current_profile.as_str())?
+    };
+    let count = fc
+        .get_profile(fc.current_profile.as_str())?
+        .mods
+        .as_ref()
+        .map_or(0, |mods| mods.len());
+
+    let records: Vec<DriftRecord> = drift.iter().map(DriftRecord::new).collect();
+    output::records(&records);
+
+    for problem in &drift {
+        let path = problem.path.display();
+        let mod_id = problem.mod_id.as_ref().map_or("", |id| id.0.as_str());
+
+        match &problem.kind {
+            DriftKind::Missing => {
+                println!(
+                    "{$bold+red}Missing:{/$} {[yellow]} [{[blue]}]",
+                    path, mod_id
+                )
+            }
+            DriftKind::Modified { actual } => println!(
+                "{$bold+red}Modified:{/$} {[yellow]} [{[blue]}] (sha1 is now {[magenta]})",
+                path, mod_id, actual
+            ),
+            DriftKind::Extra => println!("{$bold+red}Extra:{/$} {[yellow]}", path),
+        }
+    }
+
+    if drift.is_empty() {
+        println!(
+            "{$bold+green}Success:{/$} {$bold}All {} mod(s) match the manifest!{/$}",
+            count
+        );
+        Ok(())
+    } else {
+        Err(RinthaError::Drift(drift.len()))
+    }
+}
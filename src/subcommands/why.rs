@@ -0,0 +1,89 @@
+use crate::output::{self, Record};
+use bunt::println;
+use rintha::common::{ConfigMod, FullConfig, RinthaError};
+use rintha::ops::{deps, select};
+use serde::Serialize;
+
+/// Why a mod is installed, every chain goes from the mod to an explicit mod that needs it.
+#[derive(Serialize)]
+struct WhyRecord<'a> {
+    id: &'a str,
+    title: &'a str,
+    explicit: bool,
+    installed_for: Option<&'a str>,
+    chains: Vec<Vec<&'a str>>,
+}
+
+impl Record for WhyRecord<'_> {
+    const COLUMNS: &'static [&'static str] =
+        &["id", "title", "explicit", "installed_for", "chains"];
+
+    fn fields(&self) -> Vec<String> {
+        let chains: Vec<String> = self.chains.iter().map(|chain| chain.join(" <- ")).collect();
+
+        vec![
+            self.id.into(),
+            self.title.into(),
+            self.explicit.to_string(),
+            self.installed_for.unwrap_or_default().into(),
+            chains.join(";"),
+        ]
+    }
+}
+
+pub fn why(program_config: &FullConfig, target: &str) -> Result<(), RinthaError> {
+    let profile = program_config.get_profile(program_config.current_profile.as_str())?;
+    println!("{$bold}Profile:{/$} {[bold+yellow]}", profile.name);
+
+    let mods: &[ConfigMod] = profile.mods.as_deref().unwrap_or_default();
+    let ids = select(mods, &[target])?;
+    if ids.len() > 1 {
+        return Err(RinthaError::AmbiguousMod(target.into(), ids.len()));
+    }
+    // safe to unwrap, select found it
+    let idx = mods.iter().position(|cmod| cmod.id.0 == ids[0]).unwrap();
+    let cmod = &mods[idx];
+    let chains = deps::why(mods, idx);
+
+    output::record(&WhyRecord {
+        id: cmod.id.0.as_str(),
+        title: cmod.title.as_str(),
+        explicit: cmod.explicit,
+        installed_for: cmod.installed_for.as_ref().map(|id| id.0.as_str()),
+        chains: chains
+            .iter()
+            .map(|chain| chain.iter().map(|idx| mods[*idx].id.0.as_str()).collect())
+            .collect(),
+    });
+
+    if cmod.explicit {
+        println!(
+            "{$bold}{[yellow]} was installed explicitly.{/$}",
+            cmod.title
+        );
+    } else {
+        // the mod it was installed for might be gone by now
+        let reason = cmod.installed_for.as_ref().map_or("another mod", |id| {
+            mods.iter()
+                .find(|other| other.id.0 == id.0)
+                .map_or(id.0.as_str(), |other| other.title.as_str())
+        });
+        println!(
+            "{$bold}{[yellow]} was installed as a dependency of {[yellow]}.{/$}",
+            cmod.title, reason
+        );
+
+        if chains.is_empty() {
+            println!("{$bold}Nothing needs it anymore, `rintha autoremove` will remove it.{/$}");
+        } else {
+            println!("{$bold}It's needed by:{/$}");
+        }
+    }
+
+    for chain in chains.iter().filter(|chain| chain.len() > 1) {
+        let titles: Vec<&str> = chain.iter().map(|idx| mods[*idx].title.as_str()).collect();
+        println!("  {[bold+yellow]}", titles.join(" <- "));
+    }
+
+    Ok(())
+}
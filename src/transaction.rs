@@ -0,0 +1,316 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::common::{staging_dir, FullConfig, RinthaError};
+use crate::{cache, config};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Turns Ctrl-C into a flag for as long as it's alive, so a commit that's halfway done can
+/// still be rolled back instead of the process just dying.
+struct InterruptGuard {
+    previous: libc::sighandler_t,
+}
+
+impl InterruptGuard {
+    fn new() -> Self {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        let handler = on_interrupt as extern "C" fn(libc::c_int);
+        // SAFETY: the handler only touches an atomic, which is async-signal-safe
+        let previous = unsafe { libc::signal(libc::SIGINT, handler as libc::sighandler_t) };
+        InterruptGuard { previous }
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        // SAFETY: puts back whatever handler was there before `new`
+        unsafe {
+            libc::signal(libc::SIGINT, self.previous);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Change {
+    /// Copy `source` (usually something in the staging area or the cache) to `target`, or
+    /// hard link it if `link` is set
+    Place {
+        source: PathBuf,
+        target: PathBuf,
+        link: bool,
+    },
+    /// Rename `source` to `target`, which can be directories as well
+    Move {
+        source: PathBuf,
+        target: PathBuf,
+    },
+    Remove(PathBuf),
+}
+
+/// A change that was carried out, along with where the file it replaced was moved to and
+/// where `target` came from if it was moved there.
+struct Applied {
+    target: PathBuf,
+    backup: Option<PathBuf>,
+    moved_from: Option<PathBuf>,
+}
+
+/// A set of file changes across the profile directory and the mods directory that gets
+/// applied together with saving the manifest. Downloads go into `dir()` first and nothing
+/// outside of it is touched until `commit`, which undoes everything it did if any step fails
+/// or Ctrl-C is pressed.
+#[derive(Debug)]
+pub struct Transaction {
+    dir: PathBuf,
+    changes: Vec<Change>,
+}
+
+impl Transaction {
+    pub fn new() -> Result<Self, RinthaError> {
+        let staging = staging_dir()?;
+        sweep_stale(&staging);
+        let dir = staging.join(format!("tx-{}", std::process::id()));
+
+        // only a process that died with the same pid could have left this behind
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(&dir)?;
+
+        Ok(Transaction {
+            dir,
+            changes: vec![],
+        })
+    }
+
+    /// The private staging area of this transaction, it's deleted along with the transaction.
+    pub fn dir(&self) -> &Path {
+        self.dir.as_path()
+    }
+
+    pub fn place(&mut self, source: &Path, target: PathBuf) {
+        self.changes.push(Change::Place {
+            source: source.to_owned(),
+            target,
+            link: false,
+        });
+    }
+
+    /// Like `place`, but shares the file with `source` where possible. Only meant for jars
+    /// from the cache going into profile directories, which nothing ever writes to.
+    pub fn link(&mut self, source: &Path, target: PathBuf) {
+        self.changes.push(Change::Place {
+            source: source.to_owned(),
+            target,
+            link: true,
+        });
+    }
+
+    /// Moves the file or directory `source` to `target`, which have to be on the same
+    /// filesystem. Meant for things like renaming a profile directory.
+    pub fn rename(&mut self, source: PathBuf, target: PathBuf) {
+        self.changes.push(Change::Move { source, target });
+    }
+
+    /// Removing a file that doesn't exist is fine, there's just nothing to undo then. A
+    /// directory is removed along with everything in it.
+    pub fn remove(&mut self, target: PathBuf) {
+        self.changes.push(Change::Remove(target));
+    }
+
+    /// Applies every change and then saves `program_config`, if anything goes wrong all the
+    /// files are put back the way they were and the manifest stays untouched. Once the
+    /// manifest is saved the files that were replaced or removed are deleted for good.
+    pub fn commit(self, program_config: &FullConfig) -> Result<(), RinthaError> {
+        let _guard = InterruptGuard::new();
+        let mut applied = vec![];
+
+        let result = self
+            .apply(&mut applied)
+            .and_then(|_| check_interrupted())
+            .and_then(|_| config::store(program_config));
+
+        match result {
+            Ok(()) => discard_backups(applied),
+            Err(_) => rollback(applied),
+        }
+        result
+    }
+
+    /// Like `commit`, but saves `next` and only puts it in place of `program_config` once
+    /// everything went through. Operations make their changes to a copy of the config, so a
+    /// failed commit leaves the caller with a config that still matches the files.
+    pub fn commit_swap(
+        self,
+        program_config: &mut FullConfig,
+        next: FullConfig,
+    ) -> Result<(), RinthaError> {
+        self.commit(&next)?;
+        *program_config = next;
+        Ok(())
+    }
+
+    fn apply(&self, applied: &mut Vec<Applied>) -> Result<(), RinthaError> {
+        for (n, change) in self.changes.iter().enumerate() {
+            check_interrupted()?;
+
+            let target = match change {
+                Change::Place { target, .. } => target,
+                Change::Move { target, .. } => target,
+                Change::Remove(target) => target,
+            };
+
+            let backup = if target.exists() {
+                let backup = backup_path(target, n);
+                move_file(target, &backup)?;
+                Some(backup)
+            } else {
+                None
+            };
+            let moved_from = match change {
+                Change::Move { source, .. } => Some(source.clone()),
+                _ => None,
+            };
+            applied.push(Applied {
+                target: target.clone(),
+                backup,
+                moved_from,
+            });
+
+            if let Change::Move { source, target } = change {
+                fs::rename(source, target)?;
+            } else if let Change::Place {
+                source,
+                target,
+                link,
+            } = change
+            {
+                // copied next to the target first so the target never holds half a file
+                let partial =
+                    target.with_file_name(format!(".{}.rintha-partial", file_name(target)));
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                if *link {
+                    cache::link_or_copy(source, &partial)?;
+                } else {
+                    fs::copy(source, &partial)?;
+                }
+                fs::rename(&partial, target)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        // nothing useful can be done if this fails, the next transaction will clean it up
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn check_interrupted() -> Result<(), RinthaError> {
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        Err(RinthaError::Interrupted)
+    } else {
+        Ok(())
+    }
+}
+
+/// Undoes `applied` in reverse order, this is best effort since there's no one left to
+/// report a failure to.
+fn rollback(applied: Vec<Applied>) {
+    for change in applied.into_iter().rev() {
+        match change.moved_from {
+            Some(source) => {
+                let _ = fs::rename(&change.target, source);
+            }
+            None => {
+                let _ = fs::remove_file(&change.target);
+            }
+        }
+        if let Some(backup) = change.backup {
+            let _ = move_file(&backup, &change.target);
+        }
+    }
+}
+
+/// Deletes the backups of `applied` once there's no going back anymore. A backup that can't
+/// be deleted only wastes space, the change itself went through.
+fn discard_backups(applied: Vec<Applied>) {
+    for backup in applied.into_iter().filter_map(|change| change.backup) {
+        if backup.is_dir() {
+            let _ = fs::remove_dir_all(backup);
+        } else {
+            let _ = fs::remove_file(backup);
+        }
+    }
+}
+
+/// Deletes the staging dirs left behind by transactions of processes that aren't running
+/// anymore, like ones that were killed during a download.
+fn sweep_stale(staging: &Path) {
+    let entries = match fs::read_dir(staging) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let pid = match name
+            .strip_prefix("tx-")
+            .and_then(|pid| pid.parse::<u32>().ok())
+        {
+            Some(pid) => pid,
+            None => continue,
+        };
+
+        // the current pid is handled by `new` itself
+        if pid != std::process::id() && !is_running(pid) {
+            let _ = fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    // SAFETY: signal 0 only checks whether the process exists, nothing is sent
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    // EPERM means it exists but belongs to someone else
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Without a way to check, every other transaction is assumed to still be going.
+#[cfg(not(unix))]
+fn is_running(_pid: u32) -> bool {
+    true
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Backups stay in the same directory as the file so moving them is a cheap rename, which
+/// works for directories as well.
+fn backup_path(target: &Path, n: usize) -> PathBuf {
+    target.with_file_name(format!(".{}.rintha-backup-{}", file_name(target), n))
+}
+
+/// `fs::rename` that falls back to copying when the two paths are on different filesystems.
+fn move_file(from: &Path, to: &Path) -> Result<(), RinthaError> {
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}